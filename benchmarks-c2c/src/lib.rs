@@ -0,0 +1,251 @@
+//! Core-to-core latency: how long it takes a cache line to travel from one CPU to another and
+//! back, for every pair of CPUs.
+
+use benchmarks_core::{CachePadded, ProgressTracker, Statistics, allowed_cpus, pin_current_thread};
+use std::{
+    fmt::Display,
+    sync::{
+        Arc, Barrier,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Instant,
+};
+
+/// The value the initiating thread leaves in the cache line when it stops early, telling the
+/// responding thread to stop waiting for it
+const ABANDONED: u64 = u64::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// The current pair of CPUs and the total number of pairs
+    Measuring(usize, usize),
+    Done,
+}
+
+impl Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use State::*;
+        match self {
+            Measuring(pair, total) => write!(f, "Pair {pair} of {total}"),
+            Done => f.write_str("Done"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The CPUs to measure between, every CPU the process may run on if empty
+    pub cpus: Vec<usize>,
+    /// The round trips timed together as a single sample
+    pub round_trips: usize,
+    /// The samples taken for every pair of CPUs, after one untimed warm-up sample
+    pub samples: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            cpus: Vec::new(),
+            round_trips: 1000,
+            samples: 5,
+        }
+    }
+}
+
+/// The round-trip latency between every pair of a set of CPUs
+#[derive(Debug, Clone, Default)]
+pub struct LatencyMatrix {
+    /// The CPUs the rows and columns belong to, in order
+    pub cpus: Vec<usize>,
+    /// Row-major, `None` on the diagonal and wherever a thread couldn't be pinned
+    round_trips: Vec<Option<Statistics>>,
+}
+
+impl LatencyMatrix {
+    fn new(cpus: Vec<usize>) -> Self {
+        Self {
+            round_trips: vec![None; cpus.len() * cpus.len()],
+            cpus,
+        }
+    }
+    /// Statistics over the round-trip latency of every sample between the CPUs at `row` and
+    /// `column` of [`LatencyMatrix::cpus`], in nanoseconds
+    #[must_use]
+    pub fn round_trip(&self, row: usize, column: usize) -> Option<&Statistics> {
+        self.round_trips
+            .get(row * self.cpus.len() + column)
+            .and_then(Option::as_ref)
+    }
+    /// The lowest and highest median round-trip latency of any pair, in nanoseconds
+    #[must_use]
+    pub fn median_range(&self) -> Option<(f64, f64)> {
+        let medians = || self.round_trips.iter().flatten().map(|stats| stats.median);
+        let min = medians().min_by(f64::total_cmp)?;
+        let max = medians().max_by(f64::total_cmp)?;
+        Some((min, max))
+    }
+}
+
+#[derive(Debug)]
+pub struct CoreToCoreBench {
+    thread: std::thread::JoinHandle<Option<LatencyMatrix>>,
+    progress: Arc<ProgressTracker<State>>,
+}
+
+impl Config {
+    /// The CPUs [`Config::start`] measures between
+    #[must_use]
+    pub fn cpus(&self) -> Vec<usize> {
+        if self.cpus.is_empty() {
+            allowed_cpus()
+        } else {
+            self.cpus.clone()
+        }
+    }
+    /// Starts measuring the round-trip latency between every pair of CPUs.
+    ///
+    /// For every pair, one thread is pinned to each CPU and the two hand a counter in a single
+    /// cache line back and forth, each incrementing it with a compare-and-swap once it sees the
+    /// value the other one left. A round trip is the counter going to the other CPU and back.
+    /// Pairs are measured one at a time and only in one direction, the matrix is mirrored.
+    #[must_use]
+    pub fn start(self) -> CoreToCoreBench {
+        let cpus = self.cpus();
+        let pairs = cpus.len() * cpus.len().saturating_sub(1) / 2;
+        let progress = Arc::new(ProgressTracker::new(
+            pairs as u64,
+            1,
+            State::Measuring(1, pairs),
+        ));
+        let thread = {
+            let progress = Arc::clone(&progress);
+            std::thread::spawn(move || Self::run(&self, cpus, &progress))
+        };
+        CoreToCoreBench { thread, progress }
+    }
+    fn run(&self, cpus: Vec<usize>, progress: &ProgressTracker<State>) -> Option<LatencyMatrix> {
+        let mut matrix = LatencyMatrix::new(cpus);
+        let n = matrix.cpus.len();
+        let pairs = n * n.saturating_sub(1) / 2;
+        let mut pair = 0;
+        for row in 0..n {
+            for column in row + 1..n {
+                pair += 1;
+                progress.transition_state(State::Measuring(pair, pairs), pairs as u64);
+                progress.set_counter(pair as u64 - 1);
+                let round_trips =
+                    measure_pair(self, matrix.cpus[row], matrix.cpus[column], progress);
+                if progress.stop_requested() {
+                    return None;
+                }
+                let stats = Statistics::from_samples(round_trips.unwrap_or_default());
+                matrix.round_trips[row * n + column] = stats;
+                matrix.round_trips[column * n + row] = stats;
+            }
+        }
+        progress.transition_state(State::Done, pairs as u64);
+        progress.set_counter(pairs as u64);
+        Some(matrix)
+    }
+}
+
+impl CoreToCoreBench {
+    #[must_use]
+    pub fn progress(&self) -> Arc<ProgressTracker<State>> {
+        Arc::clone(&self.progress)
+    }
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        (self.progress.stop_requested() || self.progress.load_state() == State::Done)
+            && self.thread.is_finished()
+    }
+    /// The latency matrix, `None` if the benchmark was cancelled
+    #[must_use]
+    pub fn wait_for_results(self) -> Option<LatencyMatrix> {
+        self.thread.join().unwrap()
+    }
+}
+
+/// Ping-pongs a cache line between `initiator` and `responder`, returning the round-trip latency
+/// of every sample in nanoseconds, or `None` if either thread couldn't be pinned or a stop was
+/// requested
+fn measure_pair(
+    config: &Config,
+    initiator: usize,
+    responder: usize,
+    progress: &ProgressTracker<State>,
+) -> Option<Vec<f64>> {
+    // Nothing else shares the counter's cache line
+    let counter = CachePadded::<AtomicU64>::default();
+    let barrier = Barrier::new(2);
+    let pinned = [AtomicBool::new(false), AtomicBool::new(false)];
+    let round_trips = config.round_trips.max(1);
+    // The warm-up sample plus the timed ones
+    let total_round_trips = round_trips * (config.samples + 1);
+    let pin = |side: usize, cpu: usize| {
+        pinned[side].store(pin_current_thread(cpu), Ordering::Relaxed);
+        barrier.wait();
+        pinned.iter().all(|pinned| pinned.load(Ordering::Relaxed))
+    };
+    std::thread::scope(|scope| {
+        let responder = scope.spawn(|| {
+            if pin(1, responder) {
+                respond(&counter.0, total_round_trips);
+            }
+        });
+        let initiator = scope.spawn(|| {
+            if !pin(0, initiator) {
+                return None;
+            }
+            let mut samples = Vec::with_capacity(config.samples);
+            let mut value = 0;
+            initiate(&counter.0, &mut value, round_trips);
+            for _ in 0..config.samples {
+                if progress.stop_requested() {
+                    counter.0.store(ABANDONED, Ordering::Release);
+                    return None;
+                }
+                let start = Instant::now();
+                initiate(&counter.0, &mut value, round_trips);
+                let elapsed = start.elapsed();
+                samples.push(elapsed.as_nanos() as f64 / round_trips as f64);
+            }
+            Some(samples)
+        });
+        let samples = initiator.join().unwrap();
+        responder.join().unwrap();
+        samples
+    })
+}
+
+/// Runs `round_trips` round trips from the initiating side, starting at `value`, which has to be
+/// the counter's current value. Returns once the last one came back.
+///
+/// Neither side hints that it is spinning, as a pause would delay noticing the other side's
+/// write by far more than the latency being measured.
+fn initiate(counter: &AtomicU64, value: &mut u64, round_trips: usize) {
+    for _ in 0..round_trips {
+        while counter
+            .compare_exchange(*value, *value + 1, Ordering::AcqRel, Ordering::Relaxed)
+            .is_err()
+        {}
+        *value += 2;
+    }
+    while counter.load(Ordering::Acquire) != *value {}
+}
+
+/// Answers `round_trips` round trips from the responding side, or fewer if the initiating side
+/// abandoned the cache line
+fn respond(counter: &AtomicU64, round_trips: usize) {
+    let mut value = 1;
+    for _ in 0..round_trips {
+        loop {
+            match counter.compare_exchange(value, value + 1, Ordering::AcqRel, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(ABANDONED) => return,
+                Err(_) => {}
+            }
+        }
+        value += 2;
+    }
+}
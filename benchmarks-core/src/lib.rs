@@ -8,6 +8,13 @@ use std::{
 };
 
 use egui::ComboBox;
+mod affinity;
+mod padded;
+mod stats;
+pub use affinity::{allowed_cpus, pin_current_thread};
+pub use padded::CachePadded;
+pub use stats::Statistics;
+
 /// A lock-free, atomic progress bar
 /// Also used for synchronizing multiple workers to the same stages.
 #[derive(Debug)]
@@ -0,0 +1,6 @@
+/// A value alone in 128 bytes, so that no other value shares its cache line or the one next to
+/// it. Adjacent-line prefetchers pull in cache lines in pairs, so a value padded to a single
+/// cache line can still be falsely shared with its neighbor.
+#[derive(Debug, Default)]
+#[repr(align(128))]
+pub struct CachePadded<T>(pub T);
@@ -0,0 +1,136 @@
+/// Summary statistics over a set of samples, e.g. the throughput of every pass of a benchmark
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Statistics {
+    pub samples: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    /// The sample standard deviation, 0 for a single sample
+    pub std_dev: f64,
+    /// The 95th percentile, using the nearest-rank method
+    pub p95: f64,
+}
+
+impl Statistics {
+    /// Computes the statistics of `samples`, ignoring NaNs.
+    /// Returns `None` if there are no samples left.
+    #[must_use]
+    pub fn from_samples(samples: impl IntoIterator<Item = f64>) -> Option<Self> {
+        let mut sorted: Vec<f64> = samples.into_iter().filter(|s| !s.is_nan()).collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let median = if n.is_multiple_of(2) {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+        } else {
+            sorted[n / 2]
+        };
+        let std_dev = if n > 1 {
+            let squared_error: f64 = sorted.iter().map(|s| (s - mean).powi(2)).sum();
+            (squared_error / (n - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+        let p95_rank = (n * 95).div_ceil(100).max(1);
+        Some(Self {
+            samples: n,
+            min: sorted[0],
+            max: sorted[n - 1],
+            mean,
+            median,
+            std_dev,
+            p95: sorted[p95_rank - 1],
+        })
+    }
+    /// The standard deviation relative to the mean, a scale-independent measure of noise
+    #[must_use]
+    pub fn coefficient_of_variation(&self) -> f64 {
+        self.std_dev / self.mean
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn odd_sample_count() {
+        let stats = Statistics::from_samples([5.0, 1.0, 3.0, 2.0, 4.0]).unwrap();
+        assert_eq!(stats.samples, 5);
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.max, 5.0);
+        assert_close(stats.mean, 3.0);
+        assert_eq!(stats.median, 3.0);
+        // Squared errors sum to 10, over n - 1 = 4
+        assert_close(stats.std_dev, 2.5_f64.sqrt());
+        // ceil(5 * 0.95) = 5th of 5
+        assert_eq!(stats.p95, 5.0);
+        assert_close(stats.coefficient_of_variation(), 2.5_f64.sqrt() / 3.0);
+    }
+
+    #[test]
+    fn even_sample_count() {
+        let stats = Statistics::from_samples([2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]).unwrap();
+        assert_eq!(stats.samples, 8);
+        assert_close(stats.mean, 5.0);
+        // The mean of the middle two
+        assert_eq!(stats.median, 4.5);
+        // Squared errors sum to 32, over n - 1 = 7
+        assert_close(stats.std_dev, (32.0_f64 / 7.0).sqrt());
+        // ceil(8 * 0.95) = 8th of 8
+        assert_eq!(stats.p95, 9.0);
+    }
+
+    #[test]
+    fn p95_uses_the_nearest_rank() {
+        let stats = Statistics::from_samples((1..=100).rev().map(f64::from)).unwrap();
+        assert_eq!(stats.p95, 95.0);
+        assert_eq!(stats.median, 50.5);
+        let stats = Statistics::from_samples((1..=40).map(f64::from)).unwrap();
+        // ceil(40 * 0.95) = 38th of 40
+        assert_eq!(stats.p95, 38.0);
+    }
+
+    #[test]
+    fn single_sample() {
+        let stats = Statistics::from_samples([42.0]).unwrap();
+        assert_eq!(
+            stats,
+            Statistics {
+                samples: 1,
+                min: 42.0,
+                max: 42.0,
+                mean: 42.0,
+                median: 42.0,
+                std_dev: 0.0,
+                p95: 42.0,
+            }
+        );
+        assert_eq!(stats.coefficient_of_variation(), 0.0);
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(Statistics::from_samples([]), None);
+        assert_eq!(Statistics::from_samples([f64::NAN, f64::NAN]), None);
+    }
+
+    #[test]
+    fn nans_are_ignored() {
+        let stats = Statistics::from_samples([1.0, f64::NAN, 3.0]).unwrap();
+        assert_eq!(stats.samples, 2);
+        assert_eq!(stats.median, 2.0);
+        assert_close(stats.std_dev, 2.0_f64.sqrt());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{
+    Benchmark,
+    memory::{draw_pinning_options, draw_progress_bar, option_label, option_value_size},
+};
+use benchmarks_core::{BenchmarkProgressSnapshop, selectable_enum};
+use benchmarks_memory as memory;
+use eframe::egui;
+use egui_plot::{Legend, Line, Plot, Points};
+use std::time::Duration;
+
+pub struct ContentionPanel {
+    benchmark_config: memory::Config,
+    running_benchmark: Option<memory::ContentionBench>,
+    last_progress: Option<BenchmarkProgressSnapshop<memory::State>>,
+    results: Vec<memory::ContentionPoint>,
+}
+
+impl Default for ContentionPanel {
+    fn default() -> Self {
+        Self {
+            benchmark_config: memory::Config {
+                threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+                run_duration: Some(memory::DEFAULT_CONTENTION_DURATION),
+                pinning: memory::PinningPolicy::Compact,
+                ..memory::Config::default()
+            },
+            running_benchmark: None,
+            last_progress: None,
+            results: Vec::new(),
+        }
+    }
+}
+
+impl ContentionPanel {
+    fn draw_options(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("contention_benchmark_options").show(ui, |ui| {
+            let config = &mut self.benchmark_config;
+            let value_size = option_value_size(ui);
+            let label_id = option_label(ui, "Thread(s)");
+            ui.add_sized(
+                value_size,
+                egui::DragValue::new(&mut config.threads)
+                    .speed(1)
+                    .range(1..=1024),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            let label_id = option_label(ui, "Run every step for");
+            let mut milliseconds = config
+                .run_duration
+                .unwrap_or(memory::DEFAULT_CONTENTION_DURATION)
+                .as_millis() as u64;
+            let changed = ui
+                .add_sized(
+                    value_size,
+                    egui::DragValue::new(&mut milliseconds)
+                        .speed(10)
+                        .range(10..=60_000)
+                        .suffix(" ms"),
+                )
+                .labelled_by(label_id)
+                .changed();
+            if changed {
+                config.run_duration = Some(Duration::from_millis(milliseconds));
+            }
+            ui.end_row();
+            let label_id = option_label(ui, "Operation");
+            selectable_enum(
+                ui,
+                "contention_benchmark_option_operation",
+                &mut config.atomic_operation,
+                |ui| ui.width(value_size[0]),
+            )
+            .response
+            .labelled_by(label_id);
+            ui.end_row();
+            draw_pinning_options(ui, config);
+        });
+    }
+    fn draw_results(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Results");
+        Plot::new("contention_plot")
+            .height(ui.text_style_height(&egui::TextStyle::Body) * 20.0)
+            .width(ui.text_style_height(&egui::TextStyle::Body) * 40.0)
+            .legend(Legend::default())
+            .x_axis_label("Threads")
+            .y_axis_label("Mops/s")
+            .label_formatter(|name, point| {
+                format!("{name}\n{} thread(s)\n{:.1} Mops/s", point.x, point.y)
+            })
+            .include_x(1.0)
+            .include_y(0.0)
+            .show(ui, |plot| {
+                for layout in memory::CounterLayout::ALL {
+                    let points: Vec<[f64; 2]> = self
+                        .results
+                        .iter()
+                        .filter(|point| point.layout == layout)
+                        .map(|point| [point.threads as f64, point.operations_per_second() / 1e6])
+                        .collect();
+                    plot.line(Line::new(layout.as_str(), points.clone()));
+                    plot.points(Points::new(layout.as_str(), points).radius(3.0));
+                }
+            });
+        let failed_exchanges: u64 = self.results.iter().map(|p| p.failed_exchanges).sum();
+        if failed_exchanges > 0 {
+            let operations: u64 = self.results.iter().map(|p| p.operations).sum();
+            ui.label(format!(
+                "Failed compare-and-swaps: {:.1}% of attempts",
+                failed_exchanges as f64 / (failed_exchanges + operations) as f64 * 100.0
+            ));
+        }
+    }
+    fn draw_start_button(&mut self, ui: &mut egui::Ui) {
+        let start_benchmark = ui.add_visible(
+            self.running_benchmark.is_none(),
+            egui::Button::new("Start benchmark"),
+        );
+        if start_benchmark.clicked() {
+            self.running_benchmark = Some(self.benchmark_config.clone().start_contention());
+        }
+        if let Some(running) = &self.running_benchmark {
+            // Draw the Cancel button over top of the Start benchmark button, this is fine as
+            // this will only happen if the start button was invisible anyway.
+            let cancel_benchmark = ui.put(start_benchmark.rect, egui::Button::new("Cancel"));
+            if cancel_benchmark.clicked() {
+                running.progress().request_stop();
+            }
+        }
+    }
+    fn update_progress(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx();
+        if let Some(running) = self.running_benchmark.take() {
+            let progress = running.progress();
+            if running.is_done() {
+                self.results = running.wait_for_results();
+            } else {
+                self.running_benchmark = Some(running);
+                ctx.request_repaint();
+            }
+            self.last_progress = Some(progress.load());
+        }
+    }
+}
+
+impl Benchmark for ContentionPanel {
+    fn name(&self) -> &'static str {
+        "Atomic Contention"
+    }
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            self.draw_options(ui);
+            ui.separator();
+            ui.vertical(|ui| {
+                self.draw_results(ui);
+            })
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            self.draw_start_button(ui);
+            self.update_progress(ui);
+            draw_progress_bar(
+                ui,
+                self.running_benchmark.is_some(),
+                self.last_progress.as_ref(),
+            );
+        });
+    }
+}
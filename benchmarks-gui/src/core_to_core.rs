@@ -0,0 +1,215 @@
+use crate::{
+    Benchmark,
+    memory::{draw_progress_bar, option_label, option_value_size},
+};
+use benchmarks_c2c as c2c;
+use benchmarks_core::BenchmarkProgressSnapshop;
+use benchmarks_memory as memory;
+use eframe::egui;
+
+#[derive(Default)]
+pub struct CoreToCorePanel {
+    benchmark_config: c2c::Config,
+    running_benchmark: Option<c2c::CoreToCoreBench>,
+    last_progress: Option<BenchmarkProgressSnapshop<c2c::State>>,
+    results: Option<c2c::LatencyMatrix>,
+}
+
+/// The color of a latency `t` of the way from the fastest pair to the slowest one, going from
+/// blue to red
+fn heatmap_color(t: f32) -> egui::Color32 {
+    egui::ecolor::Hsva::new(0.66 * (1.0 - t.clamp(0.0, 1.0)), 0.8, 0.9, 1.0).into()
+}
+
+impl CoreToCorePanel {
+    fn draw_options(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("core_to_core_benchmark_options").show(ui, |ui| {
+            let config = &mut self.benchmark_config;
+            let value_size = option_value_size(ui);
+            let label_id = option_label(ui, "CPUs");
+            let text_id = ui.id().with("core_to_core_benchmark_option_cpus");
+            let mut text = ui
+                .data(|data| data.get_temp::<String>(text_id))
+                .unwrap_or_else(|| memory::format_cpu_list(&config.cpus));
+            let is_valid = memory::parse_cpu_list(&text).is_some();
+            let mut text_edit = egui::TextEdit::singleline(&mut text).hint_text("All");
+            if !is_valid {
+                text_edit = text_edit.text_color(ui.visuals().error_fg_color);
+            }
+            ui.add_sized(value_size, text_edit).labelled_by(label_id);
+            if let Some(cpus) = memory::parse_cpu_list(&text) {
+                config.cpus = cpus;
+            }
+            ui.data_mut(|data| data.insert_temp(text_id, text));
+            ui.end_row();
+            let label_id = option_label(ui, "Round trips");
+            ui.add_sized(
+                value_size,
+                egui::DragValue::new(&mut config.round_trips)
+                    .speed(10)
+                    .range(1..=1_000_000),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            let label_id = option_label(ui, "Samples");
+            ui.add_sized(
+                value_size,
+                egui::DragValue::new(&mut config.samples)
+                    .speed(1)
+                    .range(1..=1024),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+        });
+    }
+    fn draw_results(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Results");
+        let Some(matrix) = &self.results else {
+            ui.label("Round-trip latency between every pair of CPUs");
+            return;
+        };
+        let Some((fastest, slowest)) = matrix.median_range() else {
+            ui.label("No pair of CPUs could be measured");
+            return;
+        };
+        let n = matrix.cpus.len();
+        let text_height = ui.text_style_height(&egui::TextStyle::Body);
+        let cell = (text_height * 30.0 / n as f32).clamp(2.0, text_height * 1.5);
+        // Label every CPU if the cells are large enough, every few CPUs otherwise
+        let label_every = (text_height / cell).ceil().max(1.0) as usize;
+        let margin = text_height * 2.5;
+        let side = margin + cell * n as f32;
+        let (response, painter) = ui.allocate_painter(egui::vec2(side, side), egui::Sense::hover());
+        let origin = response.rect.min + egui::vec2(margin, margin);
+        let font = egui::TextStyle::Small.resolve(ui.style());
+        let text_color = ui.visuals().text_color();
+        for (idx, cpu) in matrix.cpus.iter().enumerate().step_by(label_every) {
+            let offset = (idx as f32 + 0.5) * cell;
+            painter.text(
+                origin + egui::vec2(offset, -2.0),
+                egui::Align2::CENTER_BOTTOM,
+                cpu.to_string(),
+                font.clone(),
+                text_color,
+            );
+            painter.text(
+                origin + egui::vec2(-4.0, offset),
+                egui::Align2::RIGHT_CENTER,
+                cpu.to_string(),
+                font.clone(),
+                text_color,
+            );
+        }
+        let range = (slowest - fastest).max(f64::EPSILON);
+        let missing = ui.visuals().faint_bg_color;
+        for row in 0..n {
+            for column in 0..n {
+                let rect = egui::Rect::from_min_size(
+                    origin + egui::vec2(column as f32, row as f32) * cell,
+                    egui::vec2(cell, cell),
+                );
+                let color = matrix.round_trip(row, column).map_or(missing, |stats| {
+                    heatmap_color(((stats.median - fastest) / range) as f32)
+                });
+                painter.rect_filled(rect, 0.0, color);
+            }
+        }
+        let hovered = response.hover_pos().and_then(|pos| {
+            let cell_pos = (pos - origin) / cell;
+            let (row, column) = (cell_pos.y.floor(), cell_pos.x.floor());
+            (row >= 0.0 && column >= 0.0 && (row as usize) < n && (column as usize) < n)
+                .then_some((row as usize, column as usize))
+        });
+        if let Some((row, column)) = hovered {
+            response.on_hover_ui_at_pointer(|ui| {
+                ui.label(format!(
+                    "CPU {} ↔ CPU {}",
+                    matrix.cpus[row], matrix.cpus[column]
+                ));
+                match matrix.round_trip(row, column) {
+                    Some(stats) => ui.label(format!(
+                        "Median: {:.1} ns\nMin: {:.1} ns\nMax: {:.1} ns",
+                        stats.median, stats.min, stats.max
+                    )),
+                    None => ui.label("Not measured"),
+                };
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label(format!("{fastest:.1} ns"));
+            let (rect, _) = ui.allocate_exact_size(
+                egui::vec2(text_height * 10.0, text_height),
+                egui::Sense::hover(),
+            );
+            let steps = 32;
+            for step in 0..steps {
+                let width = rect.width() / steps as f32;
+                let stripe = egui::Rect::from_min_size(
+                    rect.min + egui::vec2(width * step as f32, 0.0),
+                    egui::vec2(width, rect.height()),
+                );
+                ui.painter().rect_filled(
+                    stripe,
+                    0.0,
+                    heatmap_color(step as f32 / (steps - 1) as f32),
+                );
+            }
+            ui.label(format!("{slowest:.1} ns"));
+        });
+    }
+    fn draw_start_button(&mut self, ui: &mut egui::Ui) {
+        let start_benchmark = ui.add_visible(
+            self.running_benchmark.is_none(),
+            egui::Button::new("Start benchmark"),
+        );
+        if start_benchmark.clicked() {
+            self.running_benchmark = Some(self.benchmark_config.clone().start());
+        }
+        if let Some(running) = &self.running_benchmark {
+            // Draw the Cancel button over top of the Start benchmark button, this is fine as
+            // this will only happen if the start button was invisible anyway.
+            let cancel_benchmark = ui.put(start_benchmark.rect, egui::Button::new("Cancel"));
+            if cancel_benchmark.clicked() {
+                running.progress().request_stop();
+            }
+        }
+    }
+    fn update_progress(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx();
+        if let Some(running) = self.running_benchmark.take() {
+            let progress = running.progress();
+            if running.is_done() {
+                self.results = running.wait_for_results();
+            } else {
+                self.running_benchmark = Some(running);
+                ctx.request_repaint();
+            }
+            self.last_progress = Some(progress.load());
+        }
+    }
+}
+
+impl Benchmark for CoreToCorePanel {
+    fn name(&self) -> &'static str {
+        "Core-to-Core Latency"
+    }
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            self.draw_options(ui);
+            ui.separator();
+            ui.vertical(|ui| {
+                self.draw_results(ui);
+            })
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            self.draw_start_button(ui);
+            self.update_progress(ui);
+            draw_progress_bar(
+                ui,
+                self.running_benchmark.is_some(),
+                self.last_progress.as_ref(),
+            );
+        });
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    Benchmark,
+    memory::{
+        draw_memory_size_option, draw_placement_options, draw_progress_bar, option_label,
+        option_value_size,
+    },
+};
+use benchmarks_core::{BenchmarkProgressSnapshop, SelectableEnum};
+use benchmarks_memory as memory;
+use eframe::egui;
+use memory::PAGE_SIZE;
+
+pub struct FirstTouchPanel {
+    benchmark_config: memory::Config,
+    running_benchmark: Option<memory::FirstTouchBench>,
+    last_progress: Option<BenchmarkProgressSnapshop<memory::State>>,
+    results: Vec<memory::FirstTouchResult>,
+}
+
+impl Default for FirstTouchPanel {
+    fn default() -> Self {
+        Self {
+            benchmark_config: memory::Config {
+                passes: 4,
+                threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+                memory_size: *PAGE_SIZE * 1024 * 256,
+                ..memory::Config::default()
+            },
+            running_benchmark: None,
+            last_progress: None,
+            results: Vec::new(),
+        }
+    }
+}
+
+impl FirstTouchPanel {
+    fn draw_options(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("first_touch_benchmark_options").show(ui, |ui| {
+            let config = &mut self.benchmark_config;
+            let value_size = option_value_size(ui);
+            let label_id = option_label(ui, "Thread(s)");
+            ui.add_sized(
+                value_size,
+                egui::DragValue::new(&mut config.threads)
+                    .speed(1)
+                    .range(1..=1024),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            let label_id = option_label(ui, "Rounds");
+            ui.add_sized(
+                value_size,
+                egui::DragValue::new(&mut config.passes)
+                    .speed(1)
+                    .range(1..=1024),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            draw_placement_options(ui, config);
+            draw_memory_size_option(ui, config);
+        });
+    }
+    fn draw_results(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Results");
+        ui.add_enabled_ui(!self.results.is_empty(), |ui| {
+            egui::Grid::new("first_touch_benchmark_results")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Pages");
+                    ui.label("Threads");
+                    ui.label("Faults/s");
+                    ui.label("Throughput");
+                    ui.end_row();
+                    for result in &self.results {
+                        ui.label(result.backing.requested.as_str());
+                        ui.label(result.threads.to_string());
+                        ui.label(format!("{:.0}", result.faults_per_second()));
+                        ui.label(format!("{:.2} GB/s", result.throughput() / 1e9));
+                        ui.end_row();
+                    }
+                });
+            for result in self
+                .results
+                .iter()
+                .filter(|result| result.backing.fell_back())
+            {
+                ui.label(format!("{} thread(s): {}", result.threads, result.backing));
+            }
+        });
+    }
+    fn draw_start_button(&mut self, ui: &mut egui::Ui) {
+        let start_benchmark = ui.add_visible(
+            self.running_benchmark.is_none(),
+            egui::Button::new("Start benchmark"),
+        );
+        if start_benchmark.clicked() {
+            self.running_benchmark = Some(self.benchmark_config.clone().start_first_touch());
+        }
+        if let Some(running) = &self.running_benchmark {
+            // Draw the Cancel button over top of the Start benchmark button, this is fine as
+            // this will only happen if the start button was invisible anyway.
+            let cancel_benchmark = ui.put(start_benchmark.rect, egui::Button::new("Cancel"));
+            if cancel_benchmark.clicked() {
+                running.progress().request_stop();
+            }
+        }
+    }
+    fn update_progress(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx();
+        if let Some(running) = self.running_benchmark.take() {
+            let progress = running.progress();
+            if running.is_done() {
+                self.results = running.wait_for_results();
+            } else {
+                self.running_benchmark = Some(running);
+                ctx.request_repaint();
+            }
+            self.last_progress = Some(progress.load());
+        }
+    }
+}
+
+impl Benchmark for FirstTouchPanel {
+    fn name(&self) -> &'static str {
+        "First Touch"
+    }
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            self.draw_options(ui);
+            ui.separator();
+            ui.vertical(|ui| {
+                self.draw_results(ui);
+            })
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            self.draw_start_button(ui);
+            self.update_progress(ui);
+            draw_progress_bar(
+                ui,
+                self.running_benchmark.is_some(),
+                self.last_progress.as_ref(),
+            );
+        });
+    }
+}
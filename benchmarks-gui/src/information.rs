@@ -94,6 +94,8 @@ impl Benchmark for SystemInformationPanel {
                         untoggeable_label(cpu.features.avx2, "AVX2");
                         #[cfg(target_arch = "x86_64")]
                         untoggeable_label(cpu.features.avx512, "AVX512");
+                        #[cfg(target_arch = "aarch64")]
+                        untoggeable_label(cpu.features.neon, "NEON");
                     });
                 });
             }
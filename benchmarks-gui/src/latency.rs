@@ -0,0 +1,172 @@
+use crate::{
+    Benchmark,
+    memory::{
+        cpu_suffix, draw_backing_option, draw_common_options, draw_memory_size_option,
+        draw_placement_options, draw_progress_bar, draw_statistics,
+    },
+};
+use benchmarks_core::{BenchmarkProgressSnapshop, Statistics};
+use benchmarks_memory as memory;
+use eframe::{egui, emath::Float};
+use memory::PAGE_SIZE;
+
+pub struct MemoryLatencyPanel {
+    benchmark_config: memory::Config,
+    running_benchmark: Option<memory::MemoryLatencyBench>,
+    last_progress: Option<BenchmarkProgressSnapshop<memory::State>>,
+    avg_per_thread_result: memory::LatencyResult,
+    min_per_thread_result: memory::LatencyResult,
+    max_per_thread_result: memory::LatencyResult,
+    /// Statistics over the latency of every pass of every thread
+    pass_statistics: Option<Statistics>,
+}
+
+impl Default for MemoryLatencyPanel {
+    fn default() -> Self {
+        Self {
+            benchmark_config: memory::Config {
+                passes: 2,
+                threads: 1,
+                operation: memory::MemoryOperation::Read,
+                init_type: memory::MemoryInitializationType::Zeros,
+                memory_size: *PAGE_SIZE * 1024 * 64,
+                strategy: memory::OperationStrategy::Bytewise,
+                ..memory::Config::default()
+            },
+            running_benchmark: None,
+            last_progress: None,
+            avg_per_thread_result: memory::LatencyResult::default(),
+            min_per_thread_result: memory::LatencyResult::default(),
+            max_per_thread_result: memory::LatencyResult::default(),
+            pass_statistics: None,
+        }
+    }
+}
+
+impl MemoryLatencyPanel {
+    fn draw_options(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("memory_latency_benchmark_options").show(ui, |ui| {
+            draw_common_options(ui, &mut self.benchmark_config);
+            draw_placement_options(ui, &mut self.benchmark_config);
+            draw_memory_size_option(ui, &mut self.benchmark_config);
+            draw_backing_option(ui, &mut self.benchmark_config);
+        });
+    }
+    fn draw_results(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Results");
+        ui.add_enabled_ui(self.avg_per_thread_result.loads != 0, |ui| {
+            egui::Grid::new("memory_latency_benchmark_results").show(ui, |ui| {
+                ui.label("Average thread:");
+                ui.label(format!(
+                    "{:.2} ns",
+                    self.avg_per_thread_result.ns_per_load()
+                ));
+                ui.end_row();
+                ui.label("Fastest thread:");
+                ui.label(format!(
+                    "{:.2} ns{}",
+                    self.min_per_thread_result.ns_per_load(),
+                    cpu_suffix(self.min_per_thread_result.cpu)
+                ));
+                ui.end_row();
+                ui.label("Slowest thread:");
+                ui.label(format!(
+                    "{:.2} ns{}",
+                    self.max_per_thread_result.ns_per_load(),
+                    cpu_suffix(self.max_per_thread_result.cpu)
+                ));
+                ui.end_row();
+                ui.label("Buffers:");
+                ui.label(self.avg_per_thread_result.backing.to_string());
+            });
+            draw_statistics(
+                ui,
+                "memory_latency_pass_statistics",
+                self.pass_statistics.as_ref(),
+                |ns| format!("{ns:.2} ns"),
+            );
+        });
+    }
+    fn draw_start_button(&mut self, ui: &mut egui::Ui) {
+        let start_benchmark = ui.add_visible(
+            self.running_benchmark.is_none(),
+            egui::Button::new("Start benchmark"),
+        );
+        if start_benchmark.clicked() {
+            self.running_benchmark = Some(self.benchmark_config.clone().start_latency());
+        }
+        if let Some(running) = &self.running_benchmark {
+            // Draw the Cancel button over top of the Start benchmark button, this is fine as
+            // this will only happen if the start button was invisible anyway.
+            let cancel_benchmark = ui.put(start_benchmark.rect, egui::Button::new("Cancel"));
+            if cancel_benchmark.clicked() {
+                running.progress().request_stop();
+            }
+        }
+    }
+    fn update_progress(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx();
+        if let Some(running) = self.running_benchmark.take() {
+            let progress = running.progress();
+            if running.is_done() {
+                let results = running.wait_for_results();
+                self.min_per_thread_result = results
+                    .iter()
+                    .min_by_key(|r| r.ns_per_load().ord())
+                    .cloned()
+                    .unwrap_or_default();
+                self.max_per_thread_result = results
+                    .iter()
+                    .max_by_key(|r| r.ns_per_load().ord())
+                    .cloned()
+                    .unwrap_or_default();
+                let mut total_result = memory::LatencyResult {
+                    backing: results.first().map(|r| r.backing).unwrap_or_default(),
+                    ..memory::LatencyResult::default()
+                };
+                for result in &results {
+                    total_result.loads += result.loads;
+                    total_result.runtime += result.runtime;
+                    if result.backing.fell_back() {
+                        total_result.backing = result.backing;
+                    }
+                }
+                self.avg_per_thread_result = total_result;
+                self.pass_statistics = Statistics::from_samples(
+                    results
+                        .iter()
+                        .flat_map(memory::LatencyResult::pass_ns_per_load),
+                );
+            } else {
+                self.running_benchmark = Some(running);
+                ctx.request_repaint();
+            }
+            self.last_progress = Some(progress.load());
+        }
+    }
+}
+
+impl Benchmark for MemoryLatencyPanel {
+    fn name(&self) -> &'static str {
+        "Memory Latency"
+    }
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            self.draw_options(ui);
+            ui.separator();
+            ui.vertical(|ui| {
+                self.draw_results(ui);
+            })
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            self.draw_start_button(ui);
+            self.update_progress(ui);
+            draw_progress_bar(
+                ui,
+                self.running_benchmark.is_some(),
+                self.last_progress.as_ref(),
+            );
+        });
+    }
+}
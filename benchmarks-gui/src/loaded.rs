@@ -0,0 +1,183 @@
+use crate::{
+    Benchmark,
+    memory::{
+        draw_backing_option, draw_memory_size_option, draw_operation_options,
+        draw_placement_options, draw_progress_bar, option_label, option_value_size,
+    },
+};
+use benchmarks_core::BenchmarkProgressSnapshop;
+use benchmarks_memory as memory;
+use eframe::egui;
+use egui_plot::{Line, Plot, Points};
+use memory::PAGE_SIZE;
+use std::time::Duration;
+
+pub struct LoadedLatencyPanel {
+    benchmark_config: memory::Config,
+    running_benchmark: Option<memory::LoadedLatencyBench>,
+    last_progress: Option<BenchmarkProgressSnapshop<memory::State>>,
+    results: Vec<memory::LoadedLatencyPoint>,
+}
+
+impl Default for LoadedLatencyPanel {
+    fn default() -> Self {
+        Self {
+            benchmark_config: memory::Config {
+                // One thread measures latency, at least one more generates bandwidth
+                threads: std::thread::available_parallelism()
+                    .map_or(1, |threads| threads.get())
+                    .max(2),
+                memory_size: *PAGE_SIZE * 1024 * 256,
+                run_duration: Some(Duration::from_secs(1)),
+                operation: memory::MemoryOperation::Read,
+                strategy: memory::OperationStrategy::Int64,
+                pinning: memory::PinningPolicy::Compact,
+                ..memory::Config::default()
+            },
+            running_benchmark: None,
+            last_progress: None,
+            results: Vec::new(),
+        }
+    }
+}
+
+impl LoadedLatencyPanel {
+    fn draw_options(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("loaded_latency_options").show(ui, |ui| {
+            let value_size = option_value_size(ui);
+            let config = &mut self.benchmark_config;
+            let label_id = option_label(ui, "Thread(s)");
+            ui.add_sized(
+                value_size,
+                egui::DragValue::new(&mut config.threads)
+                    .speed(1)
+                    .range(2..=1024),
+            )
+            .labelled_by(label_id)
+            .on_hover_text("The first thread measures latency, all others generate bandwidth");
+            ui.end_row();
+            let label_id = option_label(ui, "Per delay");
+            let mut seconds = config.run_duration.map_or(1.0, |d| d.as_secs_f64());
+            let seconds_value = ui
+                .add_sized(
+                    value_size,
+                    egui::DragValue::new(&mut seconds)
+                        .speed(0.1)
+                        .range(0.1..=60.0)
+                        .suffix(" s"),
+                )
+                .labelled_by(label_id);
+            if seconds_value.changed() {
+                config.run_duration = Some(Duration::from_secs_f64(seconds));
+            }
+            ui.end_row();
+            draw_placement_options(ui, config);
+            draw_memory_size_option(ui, config);
+            draw_backing_option(ui, config);
+            draw_operation_options(ui, config);
+        });
+    }
+    fn draw_results(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Results");
+        let curve: Vec<[f64; 2]> = self
+            .results
+            .iter()
+            .map(|point| {
+                [
+                    point.bandwidth.wall_clock_throughput() / 1e9,
+                    point.latency.ns_per_load(),
+                ]
+            })
+            .collect();
+        Plot::new("loaded_latency_plot")
+            .height(ui.text_style_height(&egui::TextStyle::Body) * 20.0)
+            .width(ui.text_style_height(&egui::TextStyle::Body) * 40.0)
+            .x_axis_label("Bandwidth (GB/s)")
+            .y_axis_label("Latency (ns)")
+            .label_formatter(|_, point| format!("{:.2} GB/s\n{:.1} ns", point.x, point.y))
+            .include_x(0.0)
+            .include_y(0.0)
+            .show(ui, |plot| {
+                plot.line(Line::new("Latency", curve.clone()));
+                plot.points(Points::new("Injection delays", curve).radius(3.0));
+            });
+        ui.add_enabled_ui(!self.results.is_empty(), |ui| {
+            egui::Grid::new("loaded_latency_results")
+                .striped(true)
+                .show(ui, |ui| {
+                    for header in ["Delay", "Bandwidth", "Latency"] {
+                        ui.label(egui::RichText::new(header).strong());
+                    }
+                    ui.end_row();
+                    for point in &self.results {
+                        ui.label(point.injection_delay.to_string());
+                        ui.label(format!(
+                            "{:.2} GB/s",
+                            point.bandwidth.wall_clock_throughput() / 1e9
+                        ));
+                        ui.label(format!("{:.1} ns", point.latency.ns_per_load()));
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+    fn draw_start_button(&mut self, ui: &mut egui::Ui) {
+        let start_benchmark = ui.add_visible(
+            self.running_benchmark.is_none(),
+            egui::Button::new("Start benchmark"),
+        );
+        if start_benchmark.clicked() {
+            self.running_benchmark = Some(
+                self.benchmark_config
+                    .clone()
+                    .start_loaded_latency(memory::DEFAULT_INJECTION_DELAYS),
+            );
+        }
+        if let Some(running) = &self.running_benchmark {
+            // Draw the Cancel button over top of the Start benchmark button, this is fine as
+            // this will only happen if the start button was invisible anyway.
+            let cancel_benchmark = ui.put(start_benchmark.rect, egui::Button::new("Cancel"));
+            if cancel_benchmark.clicked() {
+                running.progress().request_stop();
+            }
+        }
+    }
+    fn update_progress(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx();
+        if let Some(running) = self.running_benchmark.take() {
+            let progress = running.progress();
+            if running.is_done() {
+                self.results = running.wait_for_results();
+            } else {
+                self.running_benchmark = Some(running);
+                ctx.request_repaint();
+            }
+            self.last_progress = Some(progress.load());
+        }
+    }
+}
+
+impl Benchmark for LoadedLatencyPanel {
+    fn name(&self) -> &'static str {
+        "Loaded Latency"
+    }
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            self.draw_options(ui);
+            ui.separator();
+            ui.vertical(|ui| {
+                self.draw_results(ui);
+            })
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            self.draw_start_button(ui);
+            self.update_progress(ui);
+            draw_progress_bar(
+                ui,
+                self.running_benchmark.is_some(),
+                self.last_progress.as_ref(),
+            );
+        });
+    }
+}
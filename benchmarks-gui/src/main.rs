@@ -1,10 +1,25 @@
 // hide console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
-use crate::{information::SystemInformationPanel, memory::MemoryThroughputPanel};
+use crate::{
+    contention::ContentionPanel, core_to_core::CoreToCorePanel, first_touch::FirstTouchPanel,
+    information::SystemInformationPanel, latency::MemoryLatencyPanel, loaded::LoadedLatencyPanel,
+    memory::MemoryThroughputPanel, numa::NumaMatrixPanel, prefetch::PrefetchSweepPanel,
+    sweep::MemorySweepPanel, tlb::TlbReachPanel, verify::MemoryVerificationPanel,
+};
 use eframe::egui;
 mod background_compute;
+mod contention;
+mod core_to_core;
+mod first_touch;
 mod information;
+mod latency;
+mod loaded;
 mod memory;
+mod numa;
+mod prefetch;
+mod sweep;
+mod tlb;
+mod verify;
 use tracing_subscriber::{
     EnvFilter,
     fmt::{self, format::FmtSpan},
@@ -46,6 +61,16 @@ impl MyApp {
             benchmarks: vec![
                 Box::new(SystemInformationPanel::default()),
                 Box::new(MemoryThroughputPanel::default()),
+                Box::new(MemoryLatencyPanel::default()),
+                Box::new(LoadedLatencyPanel::default()),
+                Box::new(MemorySweepPanel::default()),
+                Box::new(PrefetchSweepPanel::default()),
+                Box::new(FirstTouchPanel::default()),
+                Box::new(TlbReachPanel::default()),
+                Box::new(NumaMatrixPanel::default()),
+                Box::new(CoreToCorePanel::default()),
+                Box::new(ContentionPanel::default()),
+                Box::new(MemoryVerificationPanel::default()),
             ],
             selected_benchmark_idx: Some(0),
             selector_panel_open: true,
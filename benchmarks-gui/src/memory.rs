@@ -1,19 +1,32 @@
 use benchmarks_memory as memory;
 // hide console window on Windows in release
 use crate::Benchmark;
-use benchmarks_core::{BenchmarkProgressSnapshop, selectable_enum};
+use benchmarks_core::{BenchmarkProgressSnapshop, SelectableEnum, Statistics, selectable_enum};
 use eframe::{egui, emath::Float};
 use memory::PAGE_SIZE;
 use sizef::IntoSize;
+use std::{fmt::Display, time::Duration};
 
 pub struct MemoryThroughputPanel {
     benchmark_config: memory::Config,
     running_benchmark: Option<memory::MemoryThroughputBench>,
     last_progress: Option<BenchmarkProgressSnapshop<memory::State>>,
+    /// All threads combined, spanning the wall-clock window from the first thread starting to the
+    /// last one finishing
     total_result: memory::TestResult,
+    /// The sum of the throughput each thread measured on its own
+    sum_of_threads_throughput: f64,
     avg_per_thread_result: memory::TestResult,
     min_per_thread_result: memory::TestResult,
     max_per_thread_result: memory::TestResult,
+    /// Statistics over the combined throughput of all threads, pass by pass
+    pass_statistics: Option<Statistics>,
+    /// The configuration of the running benchmark
+    running_config: memory::Config,
+    /// The configuration the runs in `strategy_results` share, ignoring the strategy
+    comparison_config: Option<memory::Config>,
+    /// The total throughput of the latest run of every strategy and non-temporal setting
+    strategy_results: Vec<(memory::OperationStrategy, bool, f64)>,
 }
 
 impl Default for MemoryThroughputPanel {
@@ -26,13 +39,19 @@ impl Default for MemoryThroughputPanel {
                 init_type: memory::MemoryInitializationType::Zeros,
                 memory_size: *PAGE_SIZE * 1024 * 10,
                 strategy: memory::OperationStrategy::Bytewise,
+                ..memory::Config::default()
             },
             running_benchmark: None,
             last_progress: None,
             total_result: memory::TestResult::default(),
+            sum_of_threads_throughput: 0.0,
             avg_per_thread_result: memory::TestResult::default(),
             min_per_thread_result: memory::TestResult::default(),
             max_per_thread_result: memory::TestResult::default(),
+            pass_statistics: None,
+            running_config: memory::Config::default(),
+            comparison_config: None,
+            strategy_results: Vec::new(),
         }
     }
 }
@@ -40,113 +59,47 @@ impl Default for MemoryThroughputPanel {
 impl MemoryThroughputPanel {
     fn draw_options(&mut self, ui: &mut egui::Ui) {
         egui::Grid::new("memory_benchmark_options").show(ui, |ui| {
-            let height = ui.text_style_height(&egui::TextStyle::Body);
-            let valign = egui::Align::Max;
-            let value_size = [height * 6.5, height * 1.2];
-            let label_id = ui
-                .with_layout(egui::Layout::right_to_left(valign), |ui| {
-                    ui.label("Thread(s)")
-                })
-                .inner
-                .id;
-            ui.add_sized(
-                value_size,
-                egui::DragValue::new(&mut self.benchmark_config.threads)
-                    .speed(1)
-                    .range(1..=1024),
-            )
-            .labelled_by(label_id);
-            ui.end_row();
-            let label_id = ui
-                .with_layout(egui::Layout::right_to_left(valign), |ui| ui.label("Passes"))
-                .inner
-                .id;
-            ui.add_sized(
-                value_size,
-                egui::DragValue::new(&mut self.benchmark_config.passes)
-                    .speed(1)
-                    .range(1..=1024),
-            )
-            .labelled_by(label_id);
-            ui.end_row();
-            let label_id = ui
-                .with_layout(egui::Layout::right_to_left(valign), |ui| ui.label("Memory"))
-                .inner
-                .id;
-            ui.add_sized(
-                value_size,
-                egui::DragValue::new(&mut self.benchmark_config.memory_size)
-                    .speed((*PAGE_SIZE * 16) as f64)
-                    .range(
-                        *PAGE_SIZE * 4 * self.benchmark_config.threads
-                            ..=*PAGE_SIZE * 1024 * 1024 * 32,
-                    )
-                    .clamp_existing_to_range(true)
-                    .custom_formatter(|val, _| val.into_decimalsize().to_string())
-                    .custom_parser(|input| {
-                        let input = input.trim();
-                        let digits = input
-                            .bytes()
-                            .take_while(|b| matches!(b, b'0'..=b'9' | b'.'))
-                            .count();
-                        let (number, suffix) = input.split_at(digits);
-                        let multiplier = match suffix.trim_start() {
-                            "b" | "B" | "byte" => 1.0,
-                            "k" | "K" | "kb" | "KB" | "kib" | "KiB" => 1024.0,
-                            "" | "m" | "M" | "mb" | "MB" | "mib" | "MiB" => 1024.0 * 1024.0,
-                            "g" | "G" | "gb" | "GB" | "gib" | "GiB" => 1024.0 * 1024.0 * 1024.0,
-                            _ => return None,
-                        };
-                        Some(number.parse::<f64>().ok()? * multiplier)
-                    }),
-            )
-            .labelled_by(label_id);
-            ui.end_row();
-            let label_id = ui
-                .with_layout(egui::Layout::right_to_left(valign), |ui| {
-                    ui.label("Operation")
-                })
-                .inner
-                .id;
-            selectable_enum(
-                ui,
-                "memory_benchmark_option_operation",
-                &mut self.benchmark_config.operation,
-                |ui| ui.width(value_size[0]),
-            )
-            .response
-            .labelled_by(label_id);
-            ui.end_row();
-            let label_id = ui
-                .with_layout(egui::Layout::right_to_left(valign), |ui| {
-                    ui.label("Strategy")
-                })
-                .inner
-                .id;
-            selectable_enum(
-                ui,
-                "memory_benchmark_option_strategy",
-                &mut self.benchmark_config.strategy,
-                |ui| ui.width(value_size[0]),
-            )
-            .response
-            .labelled_by(label_id);
-            ui.end_row();
-            let label_id = ui
-                .with_layout(egui::Layout::right_to_left(valign), |ui| {
-                    ui.label("Fill with")
-                })
-                .inner
-                .id;
-            selectable_enum(
-                ui,
-                "memory_benchmark_option_init_type",
-                &mut self.benchmark_config.init_type,
-                |ui| ui.width(value_size[0]),
-            )
-            .response
-            .labelled_by(label_id);
-            ui.end_row();
+            draw_common_options(ui, &mut self.benchmark_config);
+            draw_placement_options(ui, &mut self.benchmark_config);
+            draw_memory_size_option(ui, &mut self.benchmark_config);
+            draw_backing_option(ui, &mut self.benchmark_config);
+            draw_operation_options(ui, &mut self.benchmark_config);
+            if self.benchmark_config.operation == memory::MemoryOperation::Copy {
+                let value_size = option_value_size(ui);
+                let label_id = option_label(ui, "Copy to");
+                selectable_enum(
+                    ui,
+                    "memory_benchmark_option_copy_placement",
+                    &mut self.benchmark_config.copy_placement,
+                    |ui| ui.width(value_size[0]),
+                )
+                .response
+                .labelled_by(label_id);
+                ui.end_row();
+            }
+            if matches!(
+                self.benchmark_config.operation,
+                memory::MemoryOperation::Read
+                    | memory::MemoryOperation::Write
+                    | memory::MemoryOperation::Mixed
+            ) && self.benchmark_config.strategy != memory::OperationStrategy::RustStd
+            {
+                let value_size = option_value_size(ui);
+                let label_id = option_label(ui, "Sharing");
+                selectable_enum(
+                    ui,
+                    "memory_benchmark_option_buffer_sharing",
+                    &mut self.benchmark_config.buffer_sharing,
+                    |ui| ui.width(value_size[0]),
+                )
+                .response
+                .labelled_by(label_id)
+                .on_hover_text(
+                    "Whether every thread works on its own buffer, or all threads on one buffer \
+                     of the whole memory size",
+                );
+                ui.end_row();
+            }
         });
     }
     fn draw_results(&mut self, ui: &mut egui::Ui) {
@@ -156,7 +109,19 @@ impl MemoryThroughputPanel {
                 ui.label("Total:");
                 ui.label(format!(
                     "{}/s",
-                    self.total_result.throughput().into_decimalsize()
+                    self.total_result.wall_clock_throughput().into_decimalsize()
+                ));
+                ui.end_row();
+                ui.label("Read + write traffic:");
+                ui.label(format!(
+                    "{}/s",
+                    self.total_result.wall_clock_traffic().into_decimalsize()
+                ));
+                ui.end_row();
+                ui.label("Sum of threads:");
+                ui.label(format!(
+                    "{}/s",
+                    self.sum_of_threads_throughput.into_decimalsize()
                 ));
                 ui.end_row();
                 ui.label("Average thread:");
@@ -167,17 +132,100 @@ impl MemoryThroughputPanel {
                 ui.end_row();
                 ui.label("Slowest thread:");
                 ui.label(format!(
-                    "{}/s",
-                    self.min_per_thread_result.throughput().into_decimalsize()
+                    "{}/s{}",
+                    self.min_per_thread_result.throughput().into_decimalsize(),
+                    cpu_suffix(self.min_per_thread_result.cpu)
                 ));
                 ui.end_row();
                 ui.label("Fastest thread:");
                 ui.label(format!(
-                    "{}/s",
-                    self.max_per_thread_result.throughput().into_decimalsize()
+                    "{}/s{}",
+                    self.max_per_thread_result.throughput().into_decimalsize(),
+                    cpu_suffix(self.max_per_thread_result.cpu)
                 ));
+                ui.end_row();
+                if self.total_result.bytes_written != 0
+                    && self.total_result.bytes_written != self.total_result.memory_traffic
+                {
+                    ui.label("Reads : writes:");
+                    ui.label(format!("{:.2} : 1", self.total_result.read_write_ratio()));
+                    ui.end_row();
+                }
+                ui.label("Buffers:");
+                ui.label(self.total_result.backing.to_string());
+                if let Some(node) = self.total_result.destination_node {
+                    ui.end_row();
+                    ui.label("Copy destination:");
+                    ui.label(format!("Node {node}"));
+                }
+            });
+            draw_statistics(
+                ui,
+                "memory_benchmark_pass_statistics",
+                self.pass_statistics.as_ref(),
+                |throughput| format!("{}/s", throughput.into_decimalsize()),
+            );
+            self.draw_strategy_comparison(ui);
+        });
+    }
+    /// Draws the latest throughput of every strategy run with the current settings, relative to
+    /// the libc baseline
+    fn draw_strategy_comparison(&self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new("Strategies").strong());
+        let baseline = self
+            .strategy_results
+            .iter()
+            .find(|&&(strategy, non_temporal, _)| {
+                strategy == memory::OperationStrategy::Libc && !non_temporal
             })
+            .map(|&(_, _, throughput)| throughput);
+        let libc_supported = self.comparison_config.as_ref().is_none_or(|config| {
+            memory::OperationStrategy::Libc.supports_operation(config.operation)
         });
+        egui::Grid::new("memory_benchmark_strategy_comparison").show(ui, |ui| {
+            for &(strategy, non_temporal, throughput) in &self.strategy_results {
+                ui.label(if non_temporal {
+                    format!("{} (non-temporal):", strategy.as_str())
+                } else {
+                    format!("{}:", strategy.as_str())
+                });
+                ui.label(format!("{}/s", throughput.into_decimalsize()));
+                ui.label(match baseline {
+                    Some(baseline) => format!("{:.2}× libc", throughput / baseline),
+                    None if !libc_supported => "libc can't run this".to_string(),
+                    None => "Run libc to compare".to_string(),
+                });
+                ui.end_row();
+            }
+        });
+    }
+    /// Records the total throughput of a finished run of `config`, forgetting the results of
+    /// other strategies if anything but the strategy changed since they ran. Runs of strategies
+    /// that can't run the operation are left out.
+    fn record_strategy_result(&mut self, config: &memory::Config, throughput: f64) {
+        if !config.strategy.supports_operation(config.operation) {
+            return;
+        }
+        let shared_config = memory::Config {
+            strategy: memory::OperationStrategy::default(),
+            non_temporal: false,
+            ..config.clone()
+        };
+        if self.comparison_config.as_ref() != Some(&shared_config) {
+            self.comparison_config = Some(shared_config);
+            self.strategy_results.clear();
+        }
+        // Strategies without a non-temporal variant ignore the toggle
+        let non_temporal = config.uses_non_temporal();
+        let key = (config.strategy, non_temporal);
+        match self
+            .strategy_results
+            .iter_mut()
+            .find(|(strategy, non_temporal, _)| (*strategy, *non_temporal) == key)
+        {
+            Some(entry) => entry.2 = throughput,
+            None => self.strategy_results.push((key.0, key.1, throughput)),
+        }
     }
     fn draw_start_button(&mut self, ui: &mut egui::Ui) {
         let start_benchmark = ui.add_visible(
@@ -185,18 +233,15 @@ impl MemoryThroughputPanel {
             egui::Button::new("Start benchmark"),
         );
         if start_benchmark.clicked() {
+            self.running_config = self.benchmark_config.clone();
             self.running_benchmark = Some(self.benchmark_config.clone().start());
         }
-        if self.running_benchmark.is_some() {
+        if let Some(running) = &self.running_benchmark {
             // Draw the Cancel button over top of the Start benchmark button, this is fine as
             // this will only happen if the start button was invisible anyway.
             let cancel_benchmark = ui.put(start_benchmark.rect, egui::Button::new("Cancel"));
             if cancel_benchmark.clicked() {
-                self.running_benchmark
-                    .as_ref()
-                    .unwrap()
-                    .progress()
-                    .request_stop();
+                running.progress().request_stop();
             }
         }
     }
@@ -209,23 +254,34 @@ impl MemoryThroughputPanel {
                 self.min_per_thread_result = results
                     .iter()
                     .min_by_key(|r| r.throughput().ord())
-                    .copied()
+                    .cloned()
                     .unwrap_or_default();
                 self.max_per_thread_result = results
                     .iter()
                     .max_by_key(|r| r.throughput().ord())
-                    .copied()
+                    .cloned()
                     .unwrap_or_default();
-                let mut total_result = memory::TestResult::default();
-                for result in &results {
-                    total_result.memory_processed += result.memory_processed;
-                    total_result.runtime += result.runtime;
+                let mut total_result = results.first().cloned().unwrap_or_default();
+                for result in results.iter().skip(1) {
+                    total_result.combine_parallel(result);
                 }
-                total_result.runtime /= results.len().max(1) as u32;
+                let threads = results.len().max(1);
                 let avg_per_thread_result = memory::TestResult {
-                    runtime: total_result.runtime,
-                    memory_processed: total_result.memory_processed / results.len().max(1),
+                    runtime: results.iter().map(|r| r.runtime).sum::<Duration>() / threads as u32,
+                    memory_processed: total_result.memory_processed / threads,
+                    memory_traffic: total_result.memory_traffic / threads,
+                    backing: total_result.backing,
+                    ..memory::TestResult::default()
                 };
+                self.sum_of_threads_throughput = results.iter().map(|r| r.throughput()).sum();
+                self.pass_statistics = total_result.pass_statistics();
+                if !results.is_empty() {
+                    let running_config = self.running_config.clone();
+                    self.record_strategy_result(
+                        &running_config,
+                        total_result.wall_clock_throughput(),
+                    );
+                }
                 self.total_result = total_result;
                 self.avg_per_thread_result = avg_per_thread_result;
             } else {
@@ -236,21 +292,10 @@ impl MemoryThroughputPanel {
         }
     }
     fn draw_progress_bar(&mut self, ui: &mut egui::Ui) {
-        let (progress, stage) = if let Some(progress) = &self.last_progress {
-            (
-                progress.as_f32(),
-                if progress.was_cancelled() {
-                    "Cancelled".to_string()
-                } else {
-                    format!("{}", progress.current_state())
-                },
-            )
-        } else {
-            (0.0, "Not running".to_string())
-        };
-        ui.add_enabled(
+        draw_progress_bar(
+            ui,
             self.running_benchmark.is_some(),
-            egui::ProgressBar::new(progress).text(stage),
+            self.last_progress.as_ref(),
         );
     }
 }
@@ -275,3 +320,405 @@ impl Benchmark for MemoryThroughputPanel {
         });
     }
 }
+
+/// The size of a single value widget in a benchmark options grid
+pub(crate) fn option_value_size(ui: &egui::Ui) -> [f32; 2] {
+    let height = ui.text_style_height(&egui::TextStyle::Body);
+    [height * 6.5, height * 1.2]
+}
+
+/// Draws a right-aligned label for an options grid row, returning its id
+pub(crate) fn option_label(ui: &mut egui::Ui, text: &str) -> egui::Id {
+    ui.with_layout(egui::Layout::right_to_left(egui::Align::Max), |ui| {
+        ui.label(text)
+    })
+    .inner
+    .id
+}
+
+/// Draws the thread count and run length rows shared by all memory benchmarks that measure with
+/// warm-up passes and a run duration
+pub(crate) fn draw_common_options(ui: &mut egui::Ui, config: &mut memory::Config) {
+    draw_threads_option(ui, config);
+    let value_size = option_value_size(ui);
+    let label_id = option_label(ui, "Warm-up passes");
+    ui.add_sized(
+        value_size,
+        egui::DragValue::new(&mut config.warmup_passes)
+            .speed(1)
+            .range(0..=1024),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+    let label_id = option_label(ui, "Run for");
+    ui.horizontal(|ui| {
+        let mut timed = config.run_duration.is_some();
+        if ui.checkbox(&mut timed, "").labelled_by(label_id).changed() {
+            config.run_duration = timed.then_some(Duration::from_secs(10));
+        }
+        let mut seconds = config.run_duration.map_or(10.0, |d| d.as_secs_f64());
+        let seconds_value = ui.add_enabled(
+            timed,
+            egui::DragValue::new(&mut seconds)
+                .speed(0.1)
+                .range(0.1..=3600.0)
+                .suffix(" s"),
+        );
+        if timed && seconds_value.changed() {
+            config.run_duration = Some(Duration::from_secs_f64(seconds));
+        }
+    });
+    ui.end_row();
+    draw_passes_option(ui, config);
+}
+
+/// Draws the thread count row
+pub(crate) fn draw_threads_option(ui: &mut egui::Ui, config: &mut memory::Config) {
+    let value_size = option_value_size(ui);
+    let label_id = option_label(ui, "Thread(s)");
+    ui.add_sized(
+        value_size,
+        egui::DragValue::new(&mut config.threads)
+            .speed(1)
+            .range(1..=1024),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+}
+
+/// Draws the pass count row, disabled while the benchmark runs for a set duration instead
+pub(crate) fn draw_passes_option(ui: &mut egui::Ui, config: &mut memory::Config) {
+    let value_size = option_value_size(ui);
+    let label_id = option_label(ui, "Passes");
+    ui.add_enabled_ui(config.run_duration.is_none(), |ui| {
+        ui.add_sized(
+            value_size,
+            egui::DragValue::new(&mut config.passes)
+                .speed(1)
+                .range(1..=1024),
+        )
+        .labelled_by(label_id);
+    });
+    ui.end_row();
+}
+
+/// Draws the rows choosing which CPUs the workers run on and where their memory is placed
+pub(crate) fn draw_placement_options(ui: &mut egui::Ui, config: &mut memory::Config) {
+    draw_pinning_options(ui, config);
+    let value_size = option_value_size(ui);
+    let label_id = option_label(ui, "Memory node");
+    let node_name = |node: Option<usize>| match node {
+        Some(node) => format!("Node {node}"),
+        None => "First touch".to_string(),
+    };
+    egui::ComboBox::from_id_salt("memory_benchmark_option_memory_node")
+        .width(value_size[0])
+        .selected_text(node_name(config.memory_node))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut config.memory_node, None, node_name(None));
+            for node in memory::NUMA_NODES.iter() {
+                ui.selectable_value(
+                    &mut config.memory_node,
+                    Some(node.id),
+                    node_name(Some(node.id)),
+                );
+            }
+        })
+        .response
+        .labelled_by(label_id);
+    ui.end_row();
+}
+
+/// Draws the rows selecting which CPUs the workers of a benchmark get pinned to
+pub(crate) fn draw_pinning_options(ui: &mut egui::Ui, config: &mut memory::Config) {
+    let value_size = option_value_size(ui);
+    let label_id = option_label(ui, "Pinning");
+    selectable_enum(
+        ui,
+        "memory_benchmark_option_pinning",
+        &mut config.pinning,
+        |ui| ui.width(value_size[0]),
+    )
+    .response
+    .labelled_by(label_id);
+    ui.end_row();
+    if config.pinning == memory::PinningPolicy::Explicit {
+        let label_id = option_label(ui, "CPUs");
+        let text_id = ui.id().with("memory_benchmark_option_pinned_cpus");
+        let mut text = ui
+            .data(|data| data.get_temp::<String>(text_id))
+            .unwrap_or_else(|| memory::format_cpu_list(&config.pinned_cpus));
+        let is_valid = memory::parse_cpu_list(&text).is_some_and(|cpus| !cpus.is_empty());
+        let mut text_edit = egui::TextEdit::singleline(&mut text).hint_text("0-3,8");
+        if !is_valid {
+            text_edit = text_edit.text_color(ui.visuals().error_fg_color);
+        }
+        ui.add_sized(value_size, text_edit).labelled_by(label_id);
+        if let Some(cpus) = memory::parse_cpu_list(&text).filter(|cpus| !cpus.is_empty()) {
+            config.pinned_cpus = cpus;
+        }
+        ui.data_mut(|data| data.insert_temp(text_id, text));
+        ui.end_row();
+    }
+}
+
+/// Draws the row selecting where the buffers of a benchmark get their memory from
+pub(crate) fn draw_backing_option(ui: &mut egui::Ui, config: &mut memory::Config) {
+    let value_size = option_value_size(ui);
+    let label_id = option_label(ui, "Backing");
+    selectable_enum(
+        ui,
+        "memory_benchmark_option_backing",
+        &mut config.backing,
+        |ui| ui.width(value_size[0]),
+    )
+    .response
+    .labelled_by(label_id);
+    ui.end_row();
+}
+
+/// Draws a grid summarizing the per-pass `statistics` of a benchmark, formatting every value that
+/// has the unit of the samples with `format`
+pub(crate) fn draw_statistics(
+    ui: &mut egui::Ui,
+    id: &str,
+    statistics: Option<&Statistics>,
+    format: impl Fn(f64) -> String,
+) {
+    ui.label(egui::RichText::new("Per-pass statistics").strong());
+    let statistics = statistics.copied().unwrap_or_default();
+    egui::Grid::new(id).show(ui, |ui| {
+        for (label, value) in [
+            ("Min:", statistics.min),
+            ("Max:", statistics.max),
+            ("Mean:", statistics.mean),
+            ("Median:", statistics.median),
+            ("Std. dev.:", statistics.std_dev),
+            ("p95:", statistics.p95),
+        ] {
+            ui.label(label);
+            ui.label(format(value));
+            ui.end_row();
+        }
+        ui.label("CV:");
+        ui.label(format!(
+            "{:.2}% over {} passes",
+            statistics.coefficient_of_variation() * 100.0,
+            statistics.samples
+        ));
+    });
+}
+
+/// Formats the CPU a worker was pinned to, for appending to a per-thread result
+pub(crate) fn cpu_suffix(cpu: Option<usize>) -> String {
+    cpu.map(|cpu| format!(" (CPU {cpu})")).unwrap_or_default()
+}
+
+/// Draws the row selecting the memory size of a benchmark
+pub(crate) fn draw_memory_size_option(ui: &mut egui::Ui, config: &mut memory::Config) {
+    let value_size = option_value_size(ui);
+    let label_id = option_label(ui, "Memory");
+    ui.add_sized(
+        value_size,
+        memory_size_drag_value(
+            &mut config.memory_size,
+            *PAGE_SIZE * 4 * config.threads..=*PAGE_SIZE * 1024 * 1024 * 32,
+        ),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+}
+
+/// Draws the operation, strategy, non-temporal, prefetch, block size, misalignment and fill rows
+/// of the throughput-style memory benchmarks
+pub(crate) fn draw_operation_options(ui: &mut egui::Ui, config: &mut memory::Config) {
+    let value_size = option_value_size(ui);
+    let label_id = option_label(ui, "Operation");
+    selectable_enum(
+        ui,
+        "memory_benchmark_option_operation",
+        &mut config.operation,
+        |ui| ui.width(value_size[0]),
+    )
+    .response
+    .labelled_by(label_id);
+    ui.end_row();
+    if config.operation == memory::MemoryOperation::Mixed {
+        let label_id = option_label(ui, "Reads : writes");
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut config.read_write_ratio.reads)
+                    .speed(0.1)
+                    .range(0..=64),
+            )
+            .labelled_by(label_id);
+            ui.label(":");
+            ui.add(
+                egui::DragValue::new(&mut config.read_write_ratio.writes)
+                    .speed(0.1)
+                    .range(0..=64),
+            )
+            .labelled_by(label_id);
+        });
+        ui.end_row();
+    }
+    let label_id = option_label(ui, "Strategy");
+    // The baselines that only copy and fill memory can't read
+    if !config.strategy.supports_operation(config.operation) {
+        config.strategy = memory::OperationStrategy::default();
+    }
+    egui::ComboBox::from_id_salt("memory_benchmark_option_strategy")
+        .width(value_size[0])
+        .selected_text(config.strategy.as_str())
+        .show_ui(ui, |ui| {
+            for &strategy in memory::OperationStrategy::all_values() {
+                let supported = strategy.supports_operation(config.operation);
+                ui.add_enabled_ui(strategy.is_enabled() && supported, |ui| {
+                    let option =
+                        ui.selectable_value(&mut config.strategy, strategy, strategy.as_str());
+                    if !supported {
+                        option.on_disabled_hover_text("Only copies and fills memory");
+                    }
+                });
+            }
+        })
+        .response
+        .labelled_by(label_id);
+    ui.end_row();
+    let label_id = option_label(ui, "Non-temporal");
+    ui.add_enabled_ui(config.strategy.supports_non_temporal(), |ui| {
+        ui.checkbox(&mut config.non_temporal, "")
+            .labelled_by(label_id)
+            .on_disabled_hover_text("Only the SIMD strategies have non-temporal variants");
+    });
+    ui.end_row();
+    if config.strategy.is_prefetching() {
+        let label_id = option_label(ui, "Prefetch hint");
+        selectable_enum(
+            ui,
+            "memory_benchmark_option_prefetch_hint",
+            &mut config.prefetch_hint,
+            |ui| ui.width(value_size[0]),
+        )
+        .response
+        .labelled_by(label_id);
+        ui.end_row();
+        let label_id = option_label(ui, "Prefetch distance");
+        ui.add_sized(
+            value_size,
+            memory_size_drag_value(&mut config.prefetch_distance, 0..=1024 * 1024)
+                .speed(memory::CACHE_LINE as f64),
+        )
+        .labelled_by(label_id)
+        .on_hover_text("How far ahead of every cache line it reads the strategy prefetches");
+        ui.end_row();
+    }
+    let label_id = option_label(ui, "Block size");
+    ui.add_sized(
+        value_size,
+        memory_size_drag_value(&mut config.block_size, 1..=1024 * 1024 * 1024)
+            .speed(memory::CACHE_LINE as f64),
+    )
+    .labelled_by(label_id)
+    .on_hover_text("The bytes the strategy processes per call");
+    ui.end_row();
+    let label_id = option_label(ui, "Misalignment");
+    ui.add_sized(
+        value_size,
+        egui::DragValue::new(&mut config.misalignment)
+            .speed(1)
+            .range(0..=*PAGE_SIZE - 1)
+            .suffix(" B"),
+    )
+    .labelled_by(label_id)
+    .on_hover_text("How many bytes past a page boundary every buffer starts");
+    ui.end_row();
+    let label_id = option_label(ui, "Access pattern");
+    selectable_enum(
+        ui,
+        "memory_benchmark_option_access_pattern",
+        &mut config.access_pattern,
+        |ui| ui.width(value_size[0]),
+    )
+    .response
+    .labelled_by(label_id);
+    ui.end_row();
+    match config.access_pattern {
+        memory::AccessPattern::Strided => {
+            let label_id = option_label(ui, "Stride");
+            ui.add_sized(
+                value_size,
+                memory_size_drag_value(&mut config.stride, memory::CACHE_LINE..=1024 * 1024 * 1024)
+                    .speed(memory::CACHE_LINE as f64),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+        }
+        memory::AccessPattern::Random => {
+            let label_id = option_label(ui, "Seed");
+            ui.add_sized(value_size, egui::DragValue::new(&mut config.pattern_seed))
+                .labelled_by(label_id);
+            ui.end_row();
+        }
+        memory::AccessPattern::Sequential | memory::AccessPattern::Reverse => {}
+    }
+    let label_id = option_label(ui, "Fill with");
+    selectable_enum(
+        ui,
+        "memory_benchmark_option_init_type",
+        &mut config.init_type,
+        |ui| ui.width(value_size[0]),
+    )
+    .response
+    .labelled_by(label_id);
+    ui.end_row();
+}
+
+/// A [`egui::DragValue`] for a size in bytes, accepting inputs such as `512 KiB` or `2G`
+pub(crate) fn memory_size_drag_value(
+    value: &mut usize,
+    range: std::ops::RangeInclusive<usize>,
+) -> egui::DragValue<'_> {
+    egui::DragValue::new(value)
+        .speed((*PAGE_SIZE * 16) as f64)
+        .range(range)
+        .clamp_existing_to_range(true)
+        .custom_formatter(|val, _| val.into_decimalsize().to_string())
+        .custom_parser(|input| {
+            let input = input.trim();
+            let digits = input
+                .bytes()
+                .take_while(|b| matches!(b, b'0'..=b'9' | b'.'))
+                .count();
+            let (number, suffix) = input.split_at(digits);
+            let multiplier = match suffix.trim_start() {
+                "b" | "B" | "byte" => 1.0,
+                "k" | "K" | "kb" | "KB" | "kib" | "KiB" => 1024.0,
+                "" | "m" | "M" | "mb" | "MB" | "mib" | "MiB" => 1024.0 * 1024.0,
+                "g" | "G" | "gb" | "GB" | "gib" | "GiB" => 1024.0 * 1024.0 * 1024.0,
+                _ => return None,
+            };
+            Some(number.parse::<f64>().ok()? * multiplier)
+        })
+}
+
+/// Draws the progress bar of a memory benchmark, greyed out while no benchmark is running
+pub(crate) fn draw_progress_bar<State: Clone + Display + PartialEq>(
+    ui: &mut egui::Ui,
+    running: bool,
+    last_progress: Option<&BenchmarkProgressSnapshop<State>>,
+) {
+    let (progress, stage) = if let Some(progress) = last_progress {
+        (
+            progress.as_f32(),
+            if progress.was_cancelled() {
+                "Cancelled".to_string()
+            } else {
+                format!("{}", progress.current_state())
+            },
+        )
+    } else {
+        (0.0, "Not running".to_string())
+    };
+    ui.add_enabled(running, egui::ProgressBar::new(progress).text(stage));
+}
@@ -0,0 +1,137 @@
+use crate::{
+    Benchmark,
+    memory::{
+        draw_backing_option, draw_common_options, draw_memory_size_option, draw_operation_options,
+        draw_progress_bar,
+    },
+};
+use benchmarks_core::BenchmarkProgressSnapshop;
+use benchmarks_memory as memory;
+use eframe::egui;
+use memory::PAGE_SIZE;
+
+pub struct NumaMatrixPanel {
+    benchmark_config: memory::Config,
+    running_benchmark: Option<memory::NumaMatrixBench>,
+    last_progress: Option<BenchmarkProgressSnapshop<memory::State>>,
+    results: Vec<memory::NumaMatrixEntry>,
+}
+
+impl Default for NumaMatrixPanel {
+    fn default() -> Self {
+        Self {
+            benchmark_config: memory::Config {
+                passes: 2,
+                threads: 1,
+                operation: memory::MemoryOperation::Read,
+                init_type: memory::MemoryInitializationType::Zeros,
+                memory_size: *PAGE_SIZE * 1024 * 64,
+                strategy: memory::OperationStrategy::Int64,
+                ..memory::Config::default()
+            },
+            running_benchmark: None,
+            last_progress: None,
+            results: Vec::new(),
+        }
+    }
+}
+
+impl NumaMatrixPanel {
+    fn draw_options(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("numa_matrix_benchmark_options").show(ui, |ui| {
+            draw_common_options(ui, &mut self.benchmark_config);
+            draw_memory_size_option(ui, &mut self.benchmark_config);
+            draw_backing_option(ui, &mut self.benchmark_config);
+            draw_operation_options(ui, &mut self.benchmark_config);
+        });
+    }
+    fn draw_results(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Results");
+        let nodes = &*memory::NUMA_NODES;
+        ui.add_enabled_ui(!self.results.is_empty(), |ui| {
+            egui::Grid::new("numa_matrix_benchmark_results")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("CPU \\ Memory");
+                    for node in nodes {
+                        ui.label(format!("Node {}", node.id));
+                    }
+                    ui.end_row();
+                    for cpu_node in nodes {
+                        ui.label(format!("Node {}", cpu_node.id));
+                        for memory_node in nodes {
+                            let entry = self.results.iter().find(|entry| {
+                                entry.cpu_node == cpu_node.id && entry.memory_node == memory_node.id
+                            });
+                            match entry {
+                                Some(entry) => ui.label(format!(
+                                    "{:.2} GB/s",
+                                    entry.result.wall_clock_throughput() / 1e9
+                                )),
+                                None => ui.label("-"),
+                            };
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(entry) = self.results.first() {
+                ui.label(format!("Buffers: {}", entry.result.backing));
+            }
+        });
+    }
+    fn draw_start_button(&mut self, ui: &mut egui::Ui) {
+        let start_benchmark = ui.add_visible(
+            self.running_benchmark.is_none(),
+            egui::Button::new("Start benchmark"),
+        );
+        if start_benchmark.clicked() {
+            self.running_benchmark = Some(self.benchmark_config.clone().start_numa_matrix());
+        }
+        if let Some(running) = &self.running_benchmark {
+            // Draw the Cancel button over top of the Start benchmark button, this is fine as
+            // this will only happen if the start button was invisible anyway.
+            let cancel_benchmark = ui.put(start_benchmark.rect, egui::Button::new("Cancel"));
+            if cancel_benchmark.clicked() {
+                running.progress().request_stop();
+            }
+        }
+    }
+    fn update_progress(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx();
+        if let Some(running) = self.running_benchmark.take() {
+            let progress = running.progress();
+            if running.is_done() {
+                self.results = running.wait_for_results();
+            } else {
+                self.running_benchmark = Some(running);
+                ctx.request_repaint();
+            }
+            self.last_progress = Some(progress.load());
+        }
+    }
+}
+
+impl Benchmark for NumaMatrixPanel {
+    fn name(&self) -> &'static str {
+        "NUMA Bandwidth"
+    }
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            self.draw_options(ui);
+            ui.separator();
+            ui.vertical(|ui| {
+                self.draw_results(ui);
+            })
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            self.draw_start_button(ui);
+            self.update_progress(ui);
+            draw_progress_bar(
+                ui,
+                self.running_benchmark.is_some(),
+                self.last_progress.as_ref(),
+            );
+        });
+    }
+}
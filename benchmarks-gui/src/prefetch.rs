@@ -0,0 +1,157 @@
+use crate::{
+    Benchmark,
+    memory::{
+        draw_backing_option, draw_memory_size_option, draw_operation_options, draw_passes_option,
+        draw_placement_options, draw_progress_bar, draw_threads_option,
+    },
+};
+use benchmarks_core::{BenchmarkProgressSnapshop, SelectableEnum};
+use benchmarks_memory as memory;
+use eframe::egui;
+use egui_plot::{Line, Plot, Points};
+use memory::PAGE_SIZE;
+use sizef::IntoSize;
+
+pub struct PrefetchSweepPanel {
+    benchmark_config: memory::Config,
+    running_benchmark: Option<memory::PrefetchSweepBench>,
+    last_progress: Option<BenchmarkProgressSnapshop<memory::State>>,
+    results: Vec<memory::PrefetchSweepPoint>,
+}
+
+impl Default for PrefetchSweepPanel {
+    fn default() -> Self {
+        Self {
+            benchmark_config: memory::Config {
+                passes: 4,
+                threads: 1,
+                operation: memory::MemoryOperation::Read,
+                memory_size: *PAGE_SIZE * 1024 * 64,
+                strategy: memory::OperationStrategy::all_values()
+                    .iter()
+                    .copied()
+                    .find(|strategy| strategy.is_prefetching() && strategy.is_enabled())
+                    .unwrap_or_default(),
+                ..memory::Config::default()
+            },
+            running_benchmark: None,
+            last_progress: None,
+            results: Vec::new(),
+        }
+    }
+}
+
+impl PrefetchSweepPanel {
+    fn draw_options(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("prefetch_sweep_options").show(ui, |ui| {
+            let config = &mut self.benchmark_config;
+            draw_threads_option(ui, config);
+            draw_passes_option(ui, config);
+            draw_placement_options(ui, config);
+            draw_memory_size_option(ui, config);
+            draw_backing_option(ui, config);
+            draw_operation_options(ui, config);
+        });
+    }
+    fn draw_results(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Results");
+        if !self.benchmark_config.strategy.is_prefetching() {
+            ui.label("The selected strategy doesn't prefetch, every distance will perform alike");
+        }
+        let throughput: Vec<[f64; 2]> = self
+            .results
+            .iter()
+            .map(|point| {
+                [
+                    (point.distance.max(1) as f64).log2(),
+                    point.result.wall_clock_throughput() / 1e9,
+                ]
+            })
+            .collect();
+        Plot::new("prefetch_sweep_plot")
+            .height(ui.text_style_height(&egui::TextStyle::Body) * 20.0)
+            .width(ui.text_style_height(&egui::TextStyle::Body) * 40.0)
+            .x_axis_label("Prefetch distance")
+            .y_axis_label("GB/s")
+            .x_axis_formatter(|mark, _| mark.value.exp2().into_decimalsize().to_string())
+            .label_formatter(|_, point| {
+                format!("{}\n{:.2} GB/s", point.x.exp2().into_decimalsize(), point.y)
+            })
+            .include_y(0.0)
+            .show(ui, |plot| {
+                plot.line(Line::new("Throughput", throughput.clone()));
+                plot.points(Points::new("Distances", throughput).radius(3.0));
+            });
+        let best = self.results.iter().max_by(|a, b| {
+            a.result
+                .wall_clock_throughput()
+                .total_cmp(&b.result.wall_clock_throughput())
+        });
+        if let Some(best) = best {
+            ui.label(format!(
+                "Best distance: {} at {:.2} GB/s",
+                best.distance.into_decimalsize(),
+                best.result.wall_clock_throughput() / 1e9
+            ));
+        }
+    }
+    fn draw_start_button(&mut self, ui: &mut egui::Ui) {
+        let start_benchmark = ui.add_visible(
+            self.running_benchmark.is_none(),
+            egui::Button::new("Start benchmark"),
+        );
+        if start_benchmark.clicked() {
+            self.running_benchmark = Some(
+                self.benchmark_config
+                    .clone()
+                    .start_prefetch_sweep(memory::DEFAULT_PREFETCH_DISTANCES),
+            );
+        }
+        if let Some(running) = &self.running_benchmark {
+            // Draw the Cancel button over top of the Start benchmark button, this is fine as
+            // this will only happen if the start button was invisible anyway.
+            let cancel_benchmark = ui.put(start_benchmark.rect, egui::Button::new("Cancel"));
+            if cancel_benchmark.clicked() {
+                running.progress().request_stop();
+            }
+        }
+    }
+    fn update_progress(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx();
+        if let Some(running) = self.running_benchmark.take() {
+            let progress = running.progress();
+            if running.is_done() {
+                self.results = running.wait_for_results();
+            } else {
+                self.running_benchmark = Some(running);
+                ctx.request_repaint();
+            }
+            self.last_progress = Some(progress.load());
+        }
+    }
+}
+
+impl Benchmark for PrefetchSweepPanel {
+    fn name(&self) -> &'static str {
+        "Prefetch Sweep"
+    }
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            self.draw_options(ui);
+            ui.separator();
+            ui.vertical(|ui| {
+                self.draw_results(ui);
+            })
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            self.draw_start_button(ui);
+            self.update_progress(ui);
+            draw_progress_bar(
+                ui,
+                self.running_benchmark.is_some(),
+                self.last_progress.as_ref(),
+            );
+        });
+    }
+}
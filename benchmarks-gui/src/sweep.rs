@@ -0,0 +1,187 @@
+use crate::{
+    Benchmark,
+    background_compute::{BackgroundCompute, BackgroundComputeProvider},
+    memory::{
+        draw_backing_option, draw_operation_options, draw_passes_option, draw_placement_options,
+        draw_progress_bar, draw_threads_option, memory_size_drag_value, option_label,
+        option_value_size,
+    },
+};
+use benchmarks_core::BenchmarkProgressSnapshop;
+use benchmarks_memory as memory;
+use benchmarks_sysinfo::cache::{CacheData, CacheType};
+use eframe::egui;
+use egui_plot::{Legend, Line, Plot, VLine};
+use memory::PAGE_SIZE;
+use sizef::IntoSize;
+
+pub struct MemorySweepPanel {
+    benchmark_config: memory::Config,
+    sweep_range: memory::SweepRange,
+    running_benchmark: Option<memory::MemorySweepBench>,
+    last_progress: Option<BenchmarkProgressSnapshop<memory::State>>,
+    results: Vec<memory::SweepPoint>,
+    caches: BackgroundCompute<CacheData, std::io::Error>,
+}
+
+impl Default for MemorySweepPanel {
+    fn default() -> Self {
+        Self {
+            benchmark_config: memory::Config {
+                passes: 2,
+                threads: 1,
+                operation: memory::MemoryOperation::Read,
+                init_type: memory::MemoryInitializationType::Zeros,
+                memory_size: *PAGE_SIZE * 1024 * 10,
+                strategy: memory::OperationStrategy::Int64,
+                ..memory::Config::default()
+            },
+            sweep_range: memory::SweepRange::default(),
+            running_benchmark: None,
+            last_progress: None,
+            results: Vec::new(),
+            caches: BackgroundCompute::new(CacheData::fetch),
+        }
+    }
+}
+
+impl MemorySweepPanel {
+    fn draw_options(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("memory_sweep_benchmark_options").show(ui, |ui| {
+            let value_size = option_value_size(ui);
+            draw_threads_option(ui, &mut self.benchmark_config);
+            draw_passes_option(ui, &mut self.benchmark_config);
+            draw_placement_options(ui, &mut self.benchmark_config);
+            let max_size = *PAGE_SIZE * 1024 * 1024 * 32;
+            let label_id = option_label(ui, "From");
+            ui.add_sized(
+                value_size,
+                memory_size_drag_value(&mut self.sweep_range.min_size, 64..=max_size),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            let label_id = option_label(ui, "To");
+            ui.add_sized(
+                value_size,
+                memory_size_drag_value(
+                    &mut self.sweep_range.max_size,
+                    self.sweep_range.min_size..=max_size,
+                ),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            let label_id = option_label(ui, "Steps per doubling");
+            ui.add_sized(
+                value_size,
+                egui::DragValue::new(&mut self.sweep_range.steps_per_doubling)
+                    .speed(1)
+                    .range(1..=16),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            draw_backing_option(ui, &mut self.benchmark_config);
+            draw_operation_options(ui, &mut self.benchmark_config);
+        });
+    }
+    fn draw_results(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Results");
+        if let Some(point) = self.results.first() {
+            ui.label(format!("Buffers: {}", point.result.backing));
+        }
+        let caches = self.caches.compute().map(|caches| &caches.caches);
+        let throughput: Vec<[f64; 2]> = self
+            .results
+            .iter()
+            .map(|point| {
+                [
+                    (point.memory_size as f64).log2(),
+                    point.result.wall_clock_throughput() / 1e9,
+                ]
+            })
+            .collect();
+        Plot::new("memory_sweep_plot")
+            .height(ui.text_style_height(&egui::TextStyle::Body) * 20.0)
+            .width(ui.text_style_height(&egui::TextStyle::Body) * 40.0)
+            .legend(Legend::default())
+            .x_axis_label("Working set")
+            .y_axis_label("GB/s")
+            .x_axis_formatter(|mark, _| mark.value.exp2().into_decimalsize().to_string())
+            .label_formatter(|_, point| {
+                format!("{}\n{:.2} GB/s", point.x.exp2().into_decimalsize(), point.y)
+            })
+            .include_y(0.0)
+            .show(ui, |plot| {
+                for cache in caches.into_iter().flatten() {
+                    let kind = match cache.cache_type {
+                        CacheType::Data => "d",
+                        CacheType::Instruction => continue,
+                        CacheType::Unified => "",
+                    };
+                    plot.vline(
+                        VLine::new(
+                            format!("L{}{kind} ({})", cache.level, cache.size.into_decimalsize()),
+                            (cache.size as f64).log2(),
+                        )
+                        .style(egui_plot::LineStyle::dashed_loose()),
+                    );
+                }
+                plot.line(Line::new("Throughput", throughput));
+            });
+    }
+    fn draw_start_button(&mut self, ui: &mut egui::Ui) {
+        let start_benchmark = ui.add_visible(
+            self.running_benchmark.is_none(),
+            egui::Button::new("Start benchmark"),
+        );
+        if start_benchmark.clicked() {
+            self.running_benchmark =
+                Some(self.benchmark_config.clone().start_sweep(&self.sweep_range));
+        }
+        if let Some(running) = &self.running_benchmark {
+            // Draw the Cancel button over top of the Start benchmark button, this is fine as
+            // this will only happen if the start button was invisible anyway.
+            let cancel_benchmark = ui.put(start_benchmark.rect, egui::Button::new("Cancel"));
+            if cancel_benchmark.clicked() {
+                running.progress().request_stop();
+            }
+        }
+    }
+    fn update_progress(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx();
+        if let Some(running) = self.running_benchmark.take() {
+            let progress = running.progress();
+            if running.is_done() {
+                self.results = running.wait_for_results();
+            } else {
+                self.running_benchmark = Some(running);
+                ctx.request_repaint();
+            }
+            self.last_progress = Some(progress.load());
+        }
+    }
+}
+
+impl Benchmark for MemorySweepPanel {
+    fn name(&self) -> &'static str {
+        "Memory Sweep"
+    }
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            self.draw_options(ui);
+            ui.separator();
+            ui.vertical(|ui| {
+                self.draw_results(ui);
+            })
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            self.draw_start_button(ui);
+            self.update_progress(ui);
+            draw_progress_bar(
+                ui,
+                self.running_benchmark.is_some(),
+                self.last_progress.as_ref(),
+            );
+        });
+    }
+}
@@ -0,0 +1,186 @@
+use crate::{
+    Benchmark,
+    memory::{
+        draw_backing_option, draw_memory_size_option, draw_placement_options, draw_progress_bar,
+        option_label, option_value_size,
+    },
+};
+use benchmarks_core::BenchmarkProgressSnapshop;
+use benchmarks_memory as memory;
+use eframe::egui;
+use egui_plot::{Legend, Line, Plot, Points, VLine};
+use memory::PAGE_SIZE;
+use sizef::IntoSize;
+
+pub struct TlbReachPanel {
+    benchmark_config: memory::Config,
+    running_benchmark: Option<memory::TlbReachBench>,
+    last_progress: Option<BenchmarkProgressSnapshop<memory::State>>,
+    results: Vec<memory::TlbReachSeries>,
+}
+
+impl Default for TlbReachPanel {
+    fn default() -> Self {
+        Self {
+            benchmark_config: memory::Config {
+                passes: 2,
+                memory_size: *PAGE_SIZE * 1024 * 256,
+                pinning: memory::PinningPolicy::Compact,
+                backing: memory::BufferBacking::TransparentHugePages,
+                ..memory::Config::default()
+            },
+            running_benchmark: None,
+            last_progress: None,
+            results: Vec::new(),
+        }
+    }
+}
+
+impl TlbReachPanel {
+    fn draw_options(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("tlb_reach_options").show(ui, |ui| {
+            let config = &mut self.benchmark_config;
+            let value_size = option_value_size(ui);
+            let label_id = option_label(ui, "Passes");
+            ui.add_sized(
+                value_size,
+                egui::DragValue::new(&mut config.passes)
+                    .speed(1)
+                    .range(1..=1024),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            draw_placement_options(ui, config);
+            draw_memory_size_option(ui, config);
+            draw_backing_option(ui, config);
+        });
+        let config = &self.benchmark_config;
+        let [small, huge] = config.tlb_reach_backings();
+        ui.label(format!(
+            "Up to {} pages of {} and {} of {}",
+            config
+                .tlb_reach_page_counts(small.page_size())
+                .last()
+                .unwrap_or(&1),
+            small.page_size().into_decimalsize(),
+            config
+                .tlb_reach_page_counts(huge.page_size())
+                .last()
+                .unwrap_or(&1),
+            huge.page_size().into_decimalsize(),
+        ));
+    }
+    fn draw_results(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Results");
+        let series: Vec<(String, Vec<[f64; 2]>, Vec<usize>)> = self
+            .results
+            .iter()
+            .map(|series| {
+                let name = format!("{} pages", series.page_size.into_decimalsize());
+                let latency = series
+                    .points
+                    .iter()
+                    .map(|point| [(point.pages as f64).log2(), point.ns_per_access()])
+                    .collect();
+                (name, latency, series.knees())
+            })
+            .collect();
+        Plot::new("tlb_reach_plot")
+            .height(ui.text_style_height(&egui::TextStyle::Body) * 20.0)
+            .width(ui.text_style_height(&egui::TextStyle::Body) * 40.0)
+            .legend(Legend::default())
+            .x_axis_label("Pages")
+            .y_axis_label("ns/access")
+            .x_axis_formatter(|mark, _| format!("{:.0}", mark.value.exp2()))
+            .label_formatter(|name, point| {
+                format!("{name}\n{:.0} pages\n{:.2} ns", point.x.exp2(), point.y)
+            })
+            .include_y(0.0)
+            .show(ui, |plot| {
+                for (name, latency, knees) in series {
+                    for pages in knees {
+                        plot.vline(
+                            VLine::new(format!("{name}: knee"), (pages as f64).log2())
+                                .style(egui_plot::LineStyle::dashed_loose()),
+                        );
+                    }
+                    plot.line(Line::new(name.clone(), latency.clone()));
+                    plot.points(Points::new(name, latency).radius(2.0));
+                }
+            });
+        for series in &self.results {
+            let knees: Vec<String> = series
+                .knees()
+                .into_iter()
+                .map(|pages| {
+                    let reach = (pages * series.page_size).into_decimalsize();
+                    format!("{pages} ({reach})")
+                })
+                .collect();
+            ui.label(format!(
+                "{}: knees at {}",
+                series.backing,
+                if knees.is_empty() {
+                    "none".to_string()
+                } else {
+                    knees.join(", ")
+                }
+            ));
+        }
+    }
+    fn draw_start_button(&mut self, ui: &mut egui::Ui) {
+        let start_benchmark = ui.add_visible(
+            self.running_benchmark.is_none(),
+            egui::Button::new("Start benchmark"),
+        );
+        if start_benchmark.clicked() {
+            self.running_benchmark = Some(self.benchmark_config.clone().start_tlb_reach());
+        }
+        if let Some(running) = &self.running_benchmark {
+            // Draw the Cancel button over top of the Start benchmark button, this is fine as
+            // this will only happen if the start button was invisible anyway.
+            let cancel_benchmark = ui.put(start_benchmark.rect, egui::Button::new("Cancel"));
+            if cancel_benchmark.clicked() {
+                running.progress().request_stop();
+            }
+        }
+    }
+    fn update_progress(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx();
+        if let Some(running) = self.running_benchmark.take() {
+            let progress = running.progress();
+            if running.is_done() {
+                self.results = running.wait_for_results();
+            } else {
+                self.running_benchmark = Some(running);
+                ctx.request_repaint();
+            }
+            self.last_progress = Some(progress.load());
+        }
+    }
+}
+
+impl Benchmark for TlbReachPanel {
+    fn name(&self) -> &'static str {
+        "TLB Reach"
+    }
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.vertical(|ui| self.draw_options(ui));
+            ui.separator();
+            ui.vertical(|ui| {
+                self.draw_results(ui);
+            })
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            self.draw_start_button(ui);
+            self.update_progress(ui);
+            draw_progress_bar(
+                ui,
+                self.running_benchmark.is_some(),
+                self.last_progress.as_ref(),
+            );
+        });
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{
+    Benchmark,
+    memory::{
+        cpu_suffix, draw_backing_option, draw_memory_size_option, draw_placement_options,
+        draw_progress_bar, option_label, option_value_size,
+    },
+};
+use benchmarks_core::{BenchmarkProgressSnapshop, SelectableEnum};
+use benchmarks_memory as memory;
+use eframe::egui;
+use memory::PAGE_SIZE;
+use sizef::IntoSize;
+
+/// The number of mismatches listed per thread, the rest are only counted
+const LISTED_MISMATCHES: usize = 32;
+
+pub struct MemoryVerificationPanel {
+    benchmark_config: memory::Config,
+    /// Whether every test of [`memory::VerificationTest::all_values`] is selected
+    selected_tests: Vec<bool>,
+    running_benchmark: Option<memory::MemoryVerificationBench>,
+    last_progress: Option<BenchmarkProgressSnapshop<memory::State>>,
+    results: Vec<memory::VerificationResult>,
+}
+
+impl Default for MemoryVerificationPanel {
+    fn default() -> Self {
+        Self {
+            benchmark_config: memory::Config {
+                passes: 1,
+                threads: 1,
+                memory_size: *PAGE_SIZE * 1024 * 64,
+                ..memory::Config::default()
+            },
+            selected_tests: vec![true; memory::VerificationTest::all_values().len()],
+            running_benchmark: None,
+            last_progress: None,
+            results: Vec::new(),
+        }
+    }
+}
+
+impl MemoryVerificationPanel {
+    fn selected_tests(&self) -> Vec<memory::VerificationTest> {
+        memory::VerificationTest::all_values()
+            .iter()
+            .zip(&self.selected_tests)
+            .filter(|(_, selected)| **selected)
+            .map(|(&test, _)| test)
+            .collect()
+    }
+    fn draw_options(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("memory_verification_options").show(ui, |ui| {
+            let value_size = option_value_size(ui);
+            let config = &mut self.benchmark_config;
+            let label_id = option_label(ui, "Thread(s)");
+            ui.add_sized(
+                value_size,
+                egui::DragValue::new(&mut config.threads)
+                    .speed(1)
+                    .range(1..=1024),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            let label_id = option_label(ui, "Repetitions");
+            ui.add_sized(
+                value_size,
+                egui::DragValue::new(&mut config.passes)
+                    .speed(1)
+                    .range(1..=1024),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            draw_placement_options(ui, config);
+            draw_memory_size_option(ui, config);
+            draw_backing_option(ui, config);
+            let label_id = option_label(ui, "Seed");
+            ui.add_sized(value_size, egui::DragValue::new(&mut config.pattern_seed))
+                .labelled_by(label_id);
+            ui.end_row();
+            option_label(ui, "Tests");
+            ui.vertical(|ui| {
+                for (test, selected) in memory::VerificationTest::all_values()
+                    .iter()
+                    .zip(&mut self.selected_tests)
+                {
+                    ui.checkbox(selected, test.as_str());
+                }
+            });
+            ui.end_row();
+        });
+    }
+    fn draw_results(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Results");
+        let Some(first) = self.results.first() else {
+            ui.label("No results yet");
+            return;
+        };
+        let bytes_tested: usize = self.results.iter().map(|r| r.bytes_tested).sum();
+        let mismatches: usize = self.results.iter().map(|r| r.mismatch_count).sum();
+        egui::Grid::new("memory_verification_results").show(ui, |ui| {
+            ui.label("Memory tested:");
+            ui.label(bytes_tested.into_decimalsize().to_string());
+            ui.end_row();
+            ui.label("Mismatches:");
+            if mismatches == 0 {
+                ui.label("None");
+            } else {
+                ui.colored_label(ui.visuals().error_fg_color, mismatches.to_string());
+            }
+            ui.end_row();
+            ui.label("Buffers:");
+            ui.label(first.backing.to_string());
+            ui.end_row();
+        });
+        for (thread, result) in self.results.iter().enumerate() {
+            if result.mismatches.is_empty() {
+                continue;
+            }
+            ui.label(
+                egui::RichText::new(format!(
+                    "Thread {thread}{}: {} mismatches",
+                    cpu_suffix(result.cpu),
+                    result.mismatch_count
+                ))
+                .strong(),
+            );
+            egui::Grid::new(("memory_verification_mismatches", thread))
+                .striped(true)
+                .show(ui, |ui| {
+                    for header in ["Test", "Offset", "Expected", "Observed"] {
+                        ui.label(egui::RichText::new(header).strong());
+                    }
+                    ui.end_row();
+                    for mismatch in result.mismatches.iter().take(LISTED_MISMATCHES) {
+                        ui.label(mismatch.test.as_str());
+                        ui.monospace(format!("{:#x}", mismatch.offset));
+                        ui.monospace(format!("{:016x}", mismatch.expected));
+                        ui.monospace(format!("{:016x}", mismatch.observed));
+                        ui.end_row();
+                    }
+                });
+        }
+    }
+    fn draw_start_button(&mut self, ui: &mut egui::Ui) {
+        let tests = self.selected_tests();
+        let start_benchmark = ui.add_visible(
+            self.running_benchmark.is_none(),
+            egui::Button::new("Start test"),
+        );
+        if start_benchmark.clicked() && !tests.is_empty() {
+            self.running_benchmark = Some(self.benchmark_config.clone().start_verification(&tests));
+        }
+        if let Some(running) = &self.running_benchmark {
+            // Draw the Cancel button over top of the Start test button, this is fine as
+            // this will only happen if the start button was invisible anyway.
+            let cancel_benchmark = ui.put(start_benchmark.rect, egui::Button::new("Cancel"));
+            if cancel_benchmark.clicked() {
+                running.progress().request_stop();
+            }
+        }
+    }
+    fn update_progress(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx();
+        if let Some(running) = self.running_benchmark.take() {
+            let progress = running.progress();
+            if running.is_done() {
+                self.results = running.wait_for_results();
+            } else {
+                self.running_benchmark = Some(running);
+                ctx.request_repaint();
+            }
+            self.last_progress = Some(progress.load());
+        }
+    }
+}
+
+impl Benchmark for MemoryVerificationPanel {
+    fn name(&self) -> &'static str {
+        "Memory Test"
+    }
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            self.draw_options(ui);
+            ui.separator();
+            ui.vertical(|ui| {
+                self.draw_results(ui);
+            })
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            self.draw_start_button(ui);
+            self.update_progress(ui);
+            draw_progress_bar(
+                ui,
+                self.running_benchmark.is_some(),
+                self.last_progress.as_ref(),
+            );
+        });
+    }
+}
@@ -0,0 +1,263 @@
+use crate::Config;
+pub(crate) use benchmarks_core::pin_current_thread;
+use benchmarks_core::{SelectableEnum, allowed_cpus};
+use std::{collections::BTreeMap, fmt::Write};
+
+/// How the worker threads of a benchmark get pinned to CPUs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PinningPolicy {
+    /// Let the scheduler place the workers
+    #[default]
+    None,
+    /// Fill the allowed CPUs core by core and package by package, putting workers on SMT siblings
+    /// before moving on
+    Compact,
+    /// Spread the workers across physical cores and packages before using SMT siblings
+    Scatter,
+    /// Use `Config::pinned_cpus`, in order
+    Explicit,
+}
+
+impl SelectableEnum for PinningPolicy {
+    fn all_values() -> &'static [Self] {
+        use PinningPolicy::*;
+        &[None, Compact, Scatter, Explicit]
+    }
+    fn as_str(&self) -> &'static str {
+        use PinningPolicy::*;
+        match self {
+            None => "None",
+            Compact => "Compact",
+            Scatter => "Scatter",
+            Explicit => "Explicit",
+        }
+    }
+}
+
+impl Config {
+    /// The CPUs workers get pinned to, in the order they are spawned in.
+    /// Worker `n` uses entry `n % len`. Empty if workers shouldn't be pinned.
+    pub(crate) fn pinning_order(&self) -> Vec<usize> {
+        match self.pinning {
+            PinningPolicy::None => Vec::new(),
+            PinningPolicy::Compact => compact_order(&allowed_cpus()),
+            PinningPolicy::Scatter => scatter_order(&allowed_cpus()),
+            PinningPolicy::Explicit => self.pinned_cpus.clone(),
+        }
+    }
+}
+
+fn read_topology_id(cpu: usize, name: &str) -> Option<usize> {
+    std::fs::read_to_string(format!("/sys/devices/system/cpu/cpu{cpu}/topology/{name}"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// The package and, if known, the physical core of `cpu`
+fn cpu_topology(cpu: usize) -> (usize, Option<usize>) {
+    let package = read_topology_id(cpu, "physical_package_id").unwrap_or(0);
+    (package, read_topology_id(cpu, "core_id"))
+}
+
+/// Orders `cpus` so that the SMT siblings of a core follow each other, core by core and package
+/// by package.
+fn compact_order(cpus: &[usize]) -> Vec<usize> {
+    compact_order_by(cpus, cpu_topology)
+}
+
+/// [`compact_order`] with the `(package, core)` of every CPU given by `topology`. CPUs with an
+/// unknown core come first in their package, in numeric order.
+fn compact_order_by(
+    cpus: &[usize],
+    topology: impl Fn(usize) -> (usize, Option<usize>),
+) -> Vec<usize> {
+    let mut order = cpus.to_vec();
+    order.sort_by_cached_key(|&cpu| (topology(cpu), cpu));
+    order
+}
+
+/// Orders `cpus` so that consecutive entries land on different physical cores, alternating
+/// between packages, and only then on the remaining SMT siblings.
+fn scatter_order(cpus: &[usize]) -> Vec<usize> {
+    scatter_order_by(cpus, |cpu| {
+        let (package, core) = cpu_topology(cpu);
+        // CPUs with unknown topology are treated as their own core
+        (package, core.unwrap_or(usize::MAX - cpu))
+    })
+}
+
+/// [`scatter_order`] with the `(package, core)` of every CPU given by `topology`
+fn scatter_order_by(cpus: &[usize], topology: impl Fn(usize) -> (usize, usize)) -> Vec<usize> {
+    // (package, core) -> the CPUs of that core
+    let mut cores: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+    for &cpu in cpus {
+        cores.entry(topology(cpu)).or_default().push(cpu);
+    }
+    let mut packages: BTreeMap<usize, Vec<&[usize]>> = BTreeMap::new();
+    for ((package, _), siblings) in &cores {
+        packages.entry(*package).or_default().push(siblings);
+    }
+    let max_cores = packages.values().map(Vec::len).max().unwrap_or(0);
+    let max_siblings = cores.values().map(Vec::len).max().unwrap_or(0);
+    let mut order = Vec::with_capacity(cpus.len());
+    for sibling in 0..max_siblings {
+        for core in 0..max_cores {
+            for package in packages.values() {
+                if let Some(&cpu) = package.get(core).and_then(|cpus| cpus.get(sibling)) {
+                    order.push(cpu);
+                }
+            }
+        }
+    }
+    order
+}
+
+/// Parses a CPU list in the kernel's format, e.g. `0-3,8,10-11`
+#[must_use]
+pub fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for range in list.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+        match range.split_once('-') {
+            Some((start, end)) => {
+                let start: usize = start.trim().parse().ok()?;
+                let end: usize = end.trim().parse().ok()?;
+                if start > end {
+                    return None;
+                }
+                cpus.extend(start..=end);
+            }
+            None => cpus.push(range.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+/// Formats `cpus` as a CPU list in the kernel's format, the inverse of [`parse_cpu_list`]
+#[must_use]
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut list = String::new();
+    let mut idx = 0;
+    while let Some(&start) = cpus.get(idx) {
+        let mut end = start;
+        while cpus.get(idx + 1) == Some(&(end + 1)) {
+            end += 1;
+            idx += 1;
+        }
+        if !list.is_empty() {
+            list.push(',');
+        }
+        if start == end {
+            _ = write!(list, "{start}");
+        } else {
+            _ = write!(list, "{start}-{end}");
+        }
+        idx += 1;
+    }
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ranges_and_single_cpus() {
+        assert_eq!(parse_cpu_list("0-3,8"), Some(vec![0, 1, 2, 3, 8]));
+        assert_eq!(parse_cpu_list("5"), Some(vec![5]));
+        assert_eq!(parse_cpu_list("2-2"), Some(vec![2]));
+    }
+
+    #[test]
+    fn parse_ignores_whitespace_and_empty_entries() {
+        assert_eq!(parse_cpu_list(" 0 - 1 , 4 ,\n"), Some(vec![0, 1, 4]));
+        assert_eq!(parse_cpu_list("1,,2"), Some(vec![1, 2]));
+        assert_eq!(parse_cpu_list(""), Some(vec![]));
+        assert_eq!(parse_cpu_list("  "), Some(vec![]));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_lists() {
+        assert_eq!(parse_cpu_list("3-1"), None);
+        assert_eq!(parse_cpu_list("a"), None);
+        assert_eq!(parse_cpu_list("1-"), None);
+        assert_eq!(parse_cpu_list("-1"), None);
+        assert_eq!(parse_cpu_list("0-1-2"), None);
+    }
+
+    #[test]
+    fn parse_keeps_duplicates_in_order() {
+        assert_eq!(parse_cpu_list("1,0-2,1"), Some(vec![1, 0, 1, 2, 1]));
+    }
+
+    #[test]
+    fn formats_runs_as_ranges() {
+        assert_eq!(format_cpu_list(&[]), "");
+        assert_eq!(format_cpu_list(&[3]), "3");
+        assert_eq!(format_cpu_list(&[0, 1, 2, 3, 8, 10, 11]), "0-3,8,10-11");
+        assert_eq!(format_cpu_list(&[4, 2, 3]), "4,2-3");
+    }
+
+    #[test]
+    fn format_round_trips_through_parse() {
+        for cpus in [
+            vec![],
+            vec![0],
+            vec![0, 1, 2, 3, 8],
+            vec![7, 6, 5],
+            vec![1, 1, 2],
+            vec![0, 2, 4, 6, 7, 8, 63],
+        ] {
+            assert_eq!(parse_cpu_list(&format_cpu_list(&cpus)), Some(cpus));
+        }
+    }
+
+    #[test]
+    fn compact_puts_smt_siblings_together() {
+        // 4 cores with 2 SMT siblings each, numbered like Linux does: cpu N and N + 4 share a core
+        let order = compact_order_by(&(0..8).collect::<Vec<_>>(), |cpu| (0, Some(cpu % 4)));
+        assert_eq!(order, [0, 4, 1, 5, 2, 6, 3, 7]);
+        // The same cores with the siblings numbered next to each other
+        let order = compact_order_by(&(0..8).collect::<Vec<_>>(), |cpu| (0, Some(cpu / 2)));
+        assert_eq!(order, [0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn compact_fills_packages_in_turn() {
+        // 2 packages with 2 cores of 2 SMT siblings each, the packages interleaved
+        let order = compact_order_by(&(0..8).collect::<Vec<_>>(), |cpu| {
+            (cpu % 2, Some(cpu % 4 / 2))
+        });
+        assert_eq!(order, [0, 4, 2, 6, 1, 5, 3, 7]);
+        // Unknown cores keep their numeric order
+        let order = compact_order_by(&[3, 1, 2, 0], |_| (0, None));
+        assert_eq!(order, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn scatter_puts_smt_siblings_last() {
+        // 4 cores with 2 SMT siblings each, numbered like Linux does: cpu N and N + 4 share a core
+        let order = scatter_order_by(&(0..8).collect::<Vec<_>>(), |cpu| (0, cpu % 4));
+        assert_eq!(order, [0, 1, 2, 3, 4, 5, 6, 7]);
+        // The same cores with the siblings numbered next to each other
+        let order = scatter_order_by(&(0..8).collect::<Vec<_>>(), |cpu| (0, cpu / 2));
+        assert_eq!(order, [0, 2, 4, 6, 1, 3, 5, 7]);
+    }
+
+    #[test]
+    fn scatter_alternates_packages() {
+        // 2 packages with 2 cores of 2 SMT siblings each
+        let order = scatter_order_by(&(0..8).collect::<Vec<_>>(), |cpu| (cpu / 4, cpu / 2));
+        assert_eq!(order, [0, 4, 2, 6, 1, 5, 3, 7]);
+    }
+
+    #[test]
+    fn scatter_keeps_every_cpu_of_uneven_topologies() {
+        let cpus = [0, 1, 2, 5, 9];
+        let mut order = scatter_order_by(&cpus, |cpu| (usize::from(cpu > 4), cpu / 2));
+        assert_eq!(order[..3], [0, 5, 2]);
+        order.sort_unstable();
+        assert_eq!(order, cpus);
+    }
+}
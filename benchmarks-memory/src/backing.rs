@@ -0,0 +1,217 @@
+use crate::{OwnedPtr, PAGE_SIZE};
+use benchmarks_core::SelectableEnum;
+use nix::sys::mman::{MapFlags, MmapAdvise, ProtFlags, madvise, mmap_anonymous, munmap};
+use std::{
+    alloc::Layout, ffi::c_void, fmt::Display, mem::MaybeUninit, num::NonZeroUsize, ptr::NonNull,
+};
+
+const HUGE_PAGE_2M: usize = 2 * 1024 * 1024;
+const HUGE_PAGE_1G: usize = 1024 * 1024 * 1024;
+const THP_ENABLED: &str = "/sys/kernel/mm/transparent_hugepage/enabled";
+
+/// Where the buffers of a benchmark get their memory from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BufferBacking {
+    /// The global allocator
+    #[default]
+    Heap,
+    /// A private anonymous mapping, faulted in on first touch
+    Mmap,
+    /// A private anonymous mapping, faulted in by the kernel up front with `MAP_POPULATE`
+    MmapPopulate,
+    /// A 2 MiB aligned anonymous mapping marked with `MADV_HUGEPAGE`
+    TransparentHugePages,
+    /// Explicit 2 MiB pages from the hugetlbfs pool
+    HugeTlb2M,
+    /// Explicit 1 GiB pages from the hugetlbfs pool
+    HugeTlb1G,
+}
+
+impl SelectableEnum for BufferBacking {
+    fn all_values() -> &'static [Self] {
+        use BufferBacking::*;
+        &[
+            Heap,
+            Mmap,
+            MmapPopulate,
+            TransparentHugePages,
+            HugeTlb2M,
+            HugeTlb1G,
+        ]
+    }
+    fn as_str(&self) -> &'static str {
+        use BufferBacking::*;
+        match self {
+            Heap => "Heap",
+            Mmap => "mmap",
+            MmapPopulate => "mmap (populated)",
+            TransparentHugePages => "Transparent huge pages",
+            HugeTlb2M => "2 MiB huge pages",
+            HugeTlb1G => "1 GiB huge pages",
+        }
+    }
+}
+
+impl BufferBacking {
+    /// The size of the pages a buffer with this backing is made of
+    #[must_use]
+    pub fn page_size(&self) -> usize {
+        use BufferBacking::*;
+        match self {
+            Heap | Mmap | MmapPopulate => *PAGE_SIZE,
+            TransparentHugePages | HugeTlb2M => HUGE_PAGE_2M,
+            HugeTlb1G => HUGE_PAGE_1G,
+        }
+    }
+}
+
+/// The backing a buffer was requested with and the one it actually got.
+/// The two differ when huge pages were unavailable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AllocatedBacking {
+    pub requested: BufferBacking,
+    pub used: BufferBacking,
+}
+
+impl AllocatedBacking {
+    #[must_use]
+    pub fn fell_back(&self) -> bool {
+        self.requested != self.used
+    }
+}
+
+impl Display for AllocatedBacking {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.used.as_str())?;
+        if self.fell_back() {
+            write!(f, " ({} unavailable)", self.requested.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// How the memory behind an [`OwnedPtr`] has to be released
+#[derive(Debug)]
+pub(crate) enum Allocation {
+    Heap { ptr: NonNull<u8>, layout: Layout },
+    Mapped { ptr: NonNull<c_void>, len: usize },
+}
+
+impl Allocation {
+    /// # Safety
+    /// Must only be called once, after which the memory may no longer be accessed
+    pub(crate) unsafe fn release(&self) {
+        match *self {
+            Allocation::Heap { ptr, layout } => unsafe {
+                std::alloc::dealloc(ptr.as_ptr(), layout)
+            },
+            Allocation::Mapped { ptr, len } => unsafe {
+                _ = munmap(ptr, len);
+            },
+        }
+    }
+    /// The whole underlying allocation, which may extend past the buffer handed out
+    pub(crate) fn as_uninit_slice(&mut self) -> &mut [MaybeUninit<u8>] {
+        let (ptr, len) = match *self {
+            Allocation::Heap { ptr, layout } => (ptr.as_ptr().cast(), layout.size()),
+            Allocation::Mapped { ptr, len } => (ptr.as_ptr().cast(), len),
+        };
+        unsafe { std::slice::from_raw_parts_mut(ptr, len) }
+    }
+}
+
+/// Allocates a page-aligned buffer of `len` bytes backed by `backing`.
+/// Falls back to a plain [`BufferBacking::Mmap`] if huge pages can't be used.
+pub(crate) fn allocate_buffer(
+    len: usize,
+    backing: BufferBacking,
+) -> (OwnedPtr<[MaybeUninit<u8>]>, AllocatedBacking) {
+    let len = len.max(1);
+    let (allocation, start, used) = match backing {
+        BufferBacking::Heap => {
+            let layout = Layout::from_size_align(len, *PAGE_SIZE)
+                .unwrap()
+                .pad_to_align();
+            let Some(ptr) = NonNull::new(unsafe { std::alloc::alloc(layout) }) else {
+                std::alloc::handle_alloc_error(layout);
+            };
+            (Allocation::Heap { ptr, layout }, ptr.cast(), backing)
+        }
+        BufferBacking::Mmap => map_plain(len, MapFlags::empty()),
+        BufferBacking::MmapPopulate => map_plain(len, MapFlags::MAP_POPULATE),
+        BufferBacking::TransparentHugePages => {
+            map_transparent_huge(len).unwrap_or_else(|| map_plain(len, MapFlags::empty()))
+        }
+        BufferBacking::HugeTlb2M => {
+            map_huge_tlb(len, HUGE_PAGE_2M, MapFlags::MAP_HUGE_2MB, backing)
+                .unwrap_or_else(|| map_plain(len, MapFlags::empty()))
+        }
+        BufferBacking::HugeTlb1G => {
+            map_huge_tlb(len, HUGE_PAGE_1G, MapFlags::MAP_HUGE_1GB, backing)
+                .unwrap_or_else(|| map_plain(len, MapFlags::empty()))
+        }
+    };
+    let ptr = std::ptr::slice_from_raw_parts_mut(start.as_ptr().cast(), len);
+    (
+        OwnedPtr { ptr, allocation },
+        AllocatedBacking {
+            requested: backing,
+            used,
+        },
+    )
+}
+
+fn map_anonymous(len: usize, flags: MapFlags) -> Option<NonNull<c_void>> {
+    unsafe {
+        mmap_anonymous(
+            None,
+            NonZeroUsize::new(len)?,
+            ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+            MapFlags::MAP_PRIVATE | flags,
+        )
+        .ok()
+    }
+}
+
+fn map_plain(len: usize, flags: MapFlags) -> (Allocation, NonNull<c_void>, BufferBacking) {
+    let Some(ptr) = map_anonymous(len, flags) else {
+        std::alloc::handle_alloc_error(Layout::from_size_align(len, *PAGE_SIZE).unwrap());
+    };
+    let backing = if flags.contains(MapFlags::MAP_POPULATE) {
+        BufferBacking::MmapPopulate
+    } else {
+        BufferBacking::Mmap
+    };
+    (Allocation::Mapped { ptr, len }, ptr, backing)
+}
+
+fn map_huge_tlb(
+    len: usize,
+    page_size: usize,
+    size_flag: MapFlags,
+    backing: BufferBacking,
+) -> Option<(Allocation, NonNull<c_void>, BufferBacking)> {
+    // hugetlbfs mappings have to be a multiple of the huge page size
+    let len = len.next_multiple_of(page_size);
+    let ptr = map_anonymous(len, MapFlags::MAP_HUGETLB | size_flag)?;
+    Some((Allocation::Mapped { ptr, len }, ptr, backing))
+}
+
+fn map_transparent_huge(len: usize) -> Option<(Allocation, NonNull<c_void>, BufferBacking)> {
+    let enabled = std::fs::read_to_string(THP_ENABLED).ok()?;
+    if enabled.contains("[never]") {
+        return None;
+    }
+    // Over-allocate so that the buffer can start on a huge page boundary
+    let huge_len = len.next_multiple_of(HUGE_PAGE_2M);
+    let map_len = huge_len + HUGE_PAGE_2M;
+    let ptr = map_anonymous(map_len, MapFlags::empty())?;
+    let allocation = Allocation::Mapped { ptr, len: map_len };
+    let offset = ptr.as_ptr().align_offset(HUGE_PAGE_2M);
+    let start = unsafe { ptr.byte_add(offset) };
+    if unsafe { madvise(start, huge_len, MmapAdvise::MADV_HUGEPAGE) }.is_err() {
+        unsafe { allocation.release() };
+        return None;
+    }
+    Some((allocation, start, BufferBacking::TransparentHugePages))
+}
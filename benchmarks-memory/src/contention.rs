@@ -0,0 +1,307 @@
+//! Atomic read-modify-write throughput on contended and falsely shared cache lines
+
+use crate::{Config, State, pin_current_thread};
+use benchmarks_core::{CachePadded, ProgressTracker, SelectableEnum};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// How long every thread count runs unless `Config::run_duration` says otherwise
+pub const DEFAULT_CONTENTION_DURATION: Duration = Duration::from_millis(250);
+
+/// The operations every thread runs between two checks of the clock
+const OPERATIONS_PER_BATCH: u64 = 1024;
+
+/// The read-modify-write operation the threads of a contention benchmark increment their
+/// counters with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AtomicOperation {
+    #[default]
+    FetchAdd,
+    /// A load followed by a compare-and-swap, retried until it succeeds
+    CompareExchange,
+}
+
+impl SelectableEnum for AtomicOperation {
+    fn all_values() -> &'static [Self] {
+        use AtomicOperation::*;
+        &[FetchAdd, CompareExchange]
+    }
+    fn as_str(&self) -> &'static str {
+        use AtomicOperation::*;
+        match self {
+            FetchAdd => "fetch_add",
+            CompareExchange => "Compare-and-swap loop",
+        }
+    }
+}
+
+/// Where the counters of the threads of a contention benchmark live
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterLayout {
+    /// All threads increment the same counter
+    Shared,
+    /// Every thread increments its own counter, with up to 8 of them packed into a cache line
+    Packed,
+    /// Every thread increments its own counter, alone in 128 bytes
+    Padded,
+}
+
+impl CounterLayout {
+    pub const ALL: [Self; 3] = [Self::Shared, Self::Packed, Self::Padded];
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        use CounterLayout::*;
+        match self {
+            Shared => "Shared counter",
+            Packed => "Packed counters",
+            Padded => "Padded counters",
+        }
+    }
+}
+
+/// The counters every layout uses, for up to `threads` threads
+#[derive(Debug)]
+struct Counters {
+    shared: CachePadded<AtomicU64>,
+    packed: Box<[PackedCounters]>,
+    padded: Box<[CachePadded<AtomicU64>]>,
+}
+
+/// A single cache line full of counters
+#[derive(Debug, Default)]
+#[repr(align(64))]
+struct PackedCounters([AtomicU64; 8]);
+
+impl Counters {
+    fn new(threads: usize) -> Self {
+        Self {
+            shared: CachePadded::default(),
+            packed: (0..threads.div_ceil(8))
+                .map(|_| Default::default())
+                .collect(),
+            padded: (0..threads).map(|_| Default::default()).collect(),
+        }
+    }
+    fn get(&self, layout: CounterLayout, worker: usize) -> &AtomicU64 {
+        match layout {
+            CounterLayout::Shared => &self.shared.0,
+            CounterLayout::Packed => &self.packed[worker / 8].0[worker % 8],
+            CounterLayout::Padded => &self.padded[worker].0,
+        }
+    }
+}
+
+/// The throughput of all threads of a single layout and thread count
+#[derive(Debug, Clone)]
+pub struct ContentionPoint {
+    pub layout: CounterLayout,
+    pub threads: usize,
+    /// The increments of all threads combined
+    pub operations: u64,
+    /// The compare-and-swaps that failed because another thread got there first, always 0 for
+    /// [`AtomicOperation::FetchAdd`]
+    pub failed_exchanges: u64,
+    /// The time from the first thread starting to the last one finishing
+    pub runtime: Duration,
+}
+
+impl ContentionPoint {
+    #[must_use]
+    pub fn operations_per_second(&self) -> f64 {
+        self.operations as f64 / self.runtime.as_secs_f64()
+    }
+}
+
+/// A single step of a single thread
+#[derive(Debug)]
+struct ContentionSample {
+    start: Instant,
+    end: Instant,
+    operations: u64,
+    failed_exchanges: u64,
+}
+
+#[derive(Debug)]
+pub struct ContentionBench {
+    config: Config,
+    steps: Arc<[(CounterLayout, usize)]>,
+    counters: Arc<Counters>,
+    /// The sample of every step of every thread, `None` for the steps a thread sat out
+    threads: Vec<std::thread::JoinHandle<Option<Vec<Option<ContentionSample>>>>>,
+    progress: Arc<ProgressTracker<State>>,
+}
+
+impl Config {
+    /// The thread counts [`Config::start_contention`] runs every layout with: powers of two up
+    /// to `threads`, then `threads` itself
+    #[must_use]
+    pub fn contention_thread_counts(&self) -> Vec<usize> {
+        let threads = self.threads.max(1);
+        let mut counts: Vec<usize> = std::iter::successors(Some(1_usize), |n| n.checked_mul(2))
+            .take_while(|&n| n < threads)
+            .collect();
+        counts.push(threads);
+        counts
+    }
+    /// Starts a contention benchmark, incrementing counters with `atomic_operation` in every
+    /// [`CounterLayout`] with every thread count of [`Config::contention_thread_counts`] in turn.
+    /// Every step runs for `run_duration`, or [`DEFAULT_CONTENTION_DURATION`] if unset.
+    ///
+    /// Only `threads`, `run_duration`, `pinning`, `pinned_cpus` and `atomic_operation` are used.
+    #[must_use]
+    pub fn start_contention(self) -> ContentionBench {
+        let progress = Arc::new(ProgressTracker::new(
+            self.threads as u64,
+            self.threads,
+            State::Allocating,
+        ));
+        let thread_counts = self.contention_thread_counts();
+        let steps = CounterLayout::ALL
+            .into_iter()
+            .flat_map(|layout| thread_counts.iter().map(move |&threads| (layout, threads)))
+            .collect();
+        let cpus = self.pinning_order();
+        let mut bench = ContentionBench {
+            counters: Arc::new(Counters::new(self.threads)),
+            config: self,
+            steps,
+            threads: Vec::new(),
+            progress,
+        };
+        for worker in 0..bench.config.threads {
+            bench.spawn_worker(worker, cpus.get(worker % cpus.len().max(1)).copied());
+        }
+        bench
+    }
+}
+
+impl ContentionBench {
+    #[must_use]
+    pub fn progress(&self) -> Arc<ProgressTracker<State>> {
+        Arc::clone(&self.progress)
+    }
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        (self.progress.stop_requested() || self.progress.load_state() == State::Done) && {
+            self.threads.iter().all(|t| t.is_finished())
+        }
+    }
+    /// The throughput of every step, layout by layout, with growing thread counts
+    #[must_use]
+    pub fn wait_for_results(self) -> Vec<ContentionPoint> {
+        let Self { threads, steps, .. } = self;
+        let mut samples = Vec::with_capacity(threads.len());
+        for thread in threads {
+            let Some(thread_samples) = thread.join().unwrap() else {
+                return Vec::new();
+            };
+            samples.push(thread_samples);
+        }
+        steps
+            .iter()
+            .enumerate()
+            .map(|(step, &(layout, threads))| {
+                let step_samples = || samples.iter().filter_map(|thread| thread[step].as_ref());
+                let start = step_samples().map(|sample| sample.start).min();
+                let end = step_samples().map(|sample| sample.end).max();
+                ContentionPoint {
+                    layout,
+                    threads,
+                    operations: step_samples().map(|sample| sample.operations).sum(),
+                    failed_exchanges: step_samples().map(|sample| sample.failed_exchanges).sum(),
+                    runtime: start
+                        .zip(end)
+                        .map_or(Duration::ZERO, |(start, end)| end - start),
+                }
+            })
+            .collect()
+    }
+    fn spawn_worker(&mut self, worker: usize, cpu: Option<usize>) {
+        let config = self.config.clone();
+        let steps = Arc::clone(&self.steps);
+        let counters = Arc::clone(&self.counters);
+        let progress = Arc::clone(&self.progress);
+        self.threads.push(std::thread::spawn(move || {
+            Self::run(&config, &steps, &counters, worker, cpu, &progress)
+        }));
+    }
+    fn run(
+        config: &Config,
+        steps: &[(CounterLayout, usize)],
+        counters: &Counters,
+        worker: usize,
+        cpu: Option<usize>,
+        progress: &Arc<ProgressTracker<State>>,
+    ) -> Option<Vec<Option<ContentionSample>>> {
+        if let Some(cpu) = cpu {
+            pin_current_thread(cpu);
+        }
+        progress.add(1);
+        let duration = config.run_duration.unwrap_or(DEFAULT_CONTENTION_DURATION);
+        let mut samples = Vec::with_capacity(steps.len());
+        for (step, &(layout, threads)) in steps.iter().enumerate() {
+            progress.transition_state(State::Contention(step + 1, steps.len()), threads as u64);
+            if progress.stop_requested() {
+                return None;
+            }
+            if worker >= threads {
+                samples.push(None);
+                continue;
+            }
+            let counter = counters.get(layout, worker);
+            let mut sample = ContentionSample {
+                start: Instant::now(),
+                end: Instant::now(),
+                operations: 0,
+                failed_exchanges: 0,
+            };
+            let deadline = sample.start + duration;
+            while sample.end < deadline && !progress.stop_requested() {
+                sample.failed_exchanges += increment(config.atomic_operation, counter);
+                sample.operations += OPERATIONS_PER_BATCH;
+                sample.end = Instant::now();
+            }
+            progress.add(1);
+            samples.push(Some(sample));
+        }
+        progress.transition_state(State::Done, config.threads as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+        progress.add(1);
+        Some(samples)
+    }
+}
+
+/// Increments `counter` [`OPERATIONS_PER_BATCH`] times with `operation`, returning the number of
+/// compare-and-swaps that failed
+fn increment(operation: AtomicOperation, counter: &AtomicU64) -> u64 {
+    let mut failed_exchanges = 0;
+    match operation {
+        AtomicOperation::FetchAdd => {
+            for _ in 0..OPERATIONS_PER_BATCH {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        AtomicOperation::CompareExchange => {
+            for _ in 0..OPERATIONS_PER_BATCH {
+                let mut current = counter.load(Ordering::Relaxed);
+                while let Err(actual) = counter.compare_exchange(
+                    current,
+                    current + 1,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    current = actual;
+                    failed_exchanges += 1;
+                }
+            }
+        }
+    }
+    failed_exchanges
+}
@@ -0,0 +1,129 @@
+use crate::{Config, NUMA_NODES, OwnedPtr, allocate_buffer, bind_to_node};
+use benchmarks_core::SelectableEnum;
+use std::{
+    mem::MaybeUninit,
+    sync::{Arc, Mutex},
+};
+
+/// Where the destination of a [`MemoryOperation::Copy`] lives relative to its source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CopyPlacement {
+    /// A buffer allocated and first touched by the copying worker
+    #[default]
+    SameThread,
+    /// The buffer of the next worker, allocated and first touched by that worker.
+    /// With a single worker this is the same as [`CopyPlacement::SameThread`].
+    OtherThread,
+    /// A buffer bound to the NUMA node after the one the source lives on.
+    /// On machines with a single node this is the same as [`CopyPlacement::SameThread`].
+    OtherNode,
+}
+
+impl SelectableEnum for CopyPlacement {
+    fn all_values() -> &'static [Self] {
+        use CopyPlacement::*;
+        &[SameThread, OtherThread, OtherNode]
+    }
+    fn as_str(&self) -> &'static str {
+        use CopyPlacement::*;
+        match self {
+            SameThread => "Same thread",
+            OtherThread => "Other thread",
+            OtherNode => "Other node",
+        }
+    }
+}
+
+/// The destination buffer of a copying worker, which other workers may copy into
+pub(crate) struct Destination {
+    memory: OwnedPtr<[u8]>,
+    /// The NUMA node the buffer was bound to, if any
+    pub node: Option<usize>,
+}
+
+// SAFETY: The buffer is only ever written by one worker at a time, see `Destination::as_mut`
+unsafe impl Send for Destination {}
+unsafe impl Sync for Destination {}
+
+impl std::fmt::Debug for Destination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Destination")
+            .field("len", &self.memory.len())
+            .field("node", &self.node)
+            .finish()
+    }
+}
+
+impl Destination {
+    /// Allocates a destination of `len` bytes with `config.backing` on the calling thread,
+    /// binding it to `node` if possible, and faults it in.
+    pub(crate) fn allocate(config: &Config, len: usize, node: Option<usize>) -> Self {
+        let (mut memory, _) = allocate_buffer(len, config.backing);
+        let node = node.filter(|&node| bind_to_node(memory.allocation.as_uninit_slice(), node));
+        for b in memory.iter_mut() {
+            b.write(0);
+        }
+        Self {
+            // SAFETY: The memory was just initialized
+            memory: unsafe {
+                core::mem::transmute::<OwnedPtr<[MaybeUninit<u8>]>, OwnedPtr<[u8]>>(memory)
+            },
+            node,
+        }
+    }
+    /// The buffer copies go into. Only the worker copying into it may access it.
+    pub(crate) fn as_ptr(&self) -> *mut [u8] {
+        self.memory.ptr
+    }
+}
+
+/// Hands the destination buffers of the workers of a benchmark to each other.
+///
+/// Every worker publishes its own destination before the state transition that ends allocation
+/// and takes the one it copies into after it, so that every worker has published by then.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DestinationExchange(Arc<Mutex<Vec<Option<Arc<Destination>>>>>);
+
+impl DestinationExchange {
+    pub(crate) fn publish(&self, worker: usize, destination: Arc<Destination>) {
+        let mut destinations = self.0.lock().unwrap();
+        if destinations.len() <= worker {
+            destinations.resize(worker + 1, None);
+        }
+        destinations[worker] = Some(destination);
+    }
+    /// The destination `worker` copies into with `config.copy_placement`
+    pub(crate) fn destination_of(&self, config: &Config, worker: usize) -> Arc<Destination> {
+        let peer = match config.copy_placement {
+            CopyPlacement::OtherThread => (worker + 1) % config.threads,
+            CopyPlacement::SameThread | CopyPlacement::OtherNode => worker,
+        };
+        let destinations = self.0.lock().unwrap();
+        Arc::clone(
+            destinations[peer]
+                .as_ref()
+                .expect("every worker publishes its destination before taking one"),
+        )
+    }
+}
+
+/// The node the destination of a worker whose source lives on `source_node` gets bound to.
+/// `None` leaves it to first touch.
+pub(crate) fn destination_node(config: &Config, source_node: Option<usize>) -> Option<usize> {
+    if config.copy_placement != CopyPlacement::OtherNode {
+        return source_node;
+    }
+    let source_node = source_node.or_else(current_node)?;
+    let nodes = &*NUMA_NODES;
+    let idx = nodes.iter().position(|node| node.id == source_node)?;
+    Some(nodes[(idx + 1) % nodes.len()].id)
+}
+
+/// The NUMA node of the CPU the calling thread is running on
+fn current_node() -> Option<usize> {
+    let cpu = usize::try_from(unsafe { libc::sched_getcpu() }).ok()?;
+    NUMA_NODES
+        .iter()
+        .find(|node| node.cpus.contains(&cpu))
+        .map(|node| node.id)
+}
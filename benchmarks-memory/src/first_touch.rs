@@ -0,0 +1,272 @@
+//! Faulting in fresh anonymous mappings, the cost every buffer pays on first touch and which the
+//! allocation and initialization phases of the other benchmarks leave unmeasured.
+
+use crate::{
+    AllocatedBacking, BufferBacking, Config, PAGE_SIZE, State, allocate_bound_buffer,
+    pin_current_thread,
+};
+use benchmarks_core::ProgressTracker;
+use std::{
+    mem::MaybeUninit,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// The number of pages touched between two progress updates
+const PAGES_PER_PROGRESS_UPDATE: usize = 256;
+
+/// A single way of faulting in memory a first-touch benchmark measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirstTouchCase {
+    pub backing: BufferBacking,
+    /// The number of threads faulting in memory at the same time, each in its own mapping
+    pub threads: usize,
+}
+
+/// How fast the threads of a [`FirstTouchCase`] faulted in their mappings
+#[derive(Debug, Clone, Default)]
+pub struct FirstTouchResult {
+    /// The number of threads faulting in memory at the same time
+    pub threads: usize,
+    pub backing: AllocatedBacking,
+    /// The bytes of all mappings faulted in, over all rounds
+    pub bytes_touched: usize,
+    /// The minor page faults the threads took while touching their mappings
+    pub page_faults: u64,
+    /// The time from the first thread starting to touch its mapping to the last one finishing,
+    /// summed over all rounds
+    pub runtime: Duration,
+}
+
+impl FirstTouchResult {
+    #[must_use]
+    pub fn faults_per_second(&self) -> f64 {
+        self.page_faults as f64 / self.runtime.as_secs_f64()
+    }
+    /// The bytes faulted in per second
+    #[must_use]
+    pub fn throughput(&self) -> f64 {
+        self.bytes_touched as f64 / self.runtime.as_secs_f64()
+    }
+}
+
+/// A single round of a single thread
+#[derive(Debug)]
+struct TouchSample {
+    start: Instant,
+    end: Instant,
+    bytes: usize,
+    page_faults: u64,
+    backing: AllocatedBacking,
+}
+
+#[derive(Debug)]
+pub struct FirstTouchBench {
+    config: Config,
+    cases: Arc<[FirstTouchCase]>,
+    /// The samples of every case of every thread, empty for the cases a thread sat out
+    threads: Vec<std::thread::JoinHandle<Option<Vec<Vec<TouchSample>>>>>,
+    progress: Arc<ProgressTracker<State>>,
+}
+
+impl Config {
+    /// The cases [`Config::start_first_touch`] measures: plain 4 KiB mappings and transparent
+    /// huge pages, each faulted in by a single thread and by `threads` threads at once
+    #[must_use]
+    pub fn first_touch_cases(&self) -> Vec<FirstTouchCase> {
+        let mut thread_counts = vec![1, self.threads.max(1)];
+        thread_counts.dedup();
+        [BufferBacking::Mmap, BufferBacking::TransparentHugePages]
+            .into_iter()
+            .flat_map(|backing| {
+                thread_counts
+                    .iter()
+                    .map(move |&threads| FirstTouchCase { backing, threads })
+            })
+            .collect()
+    }
+    /// Starts a first-touch benchmark running every case of [`Config::first_touch_cases`].
+    ///
+    /// For every one of `passes` rounds, every thread of a case maps its share of `memory_size`
+    /// bytes, then all of them write a byte to every page of their mapping at once, so that
+    /// concurrent faults contend on the process' `mmap_lock` and page tables. Only touching the
+    /// mappings is timed, mapping and unmapping them is not.
+    ///
+    /// `backing` is ignored, `operation`, `strategy`, `warmup_passes` and `run_duration` as well.
+    #[must_use]
+    pub fn start_first_touch(self) -> FirstTouchBench {
+        let progress = Arc::new(ProgressTracker::new(
+            self.threads as u64,
+            self.threads,
+            State::Allocating,
+        ));
+        let cpus = self.pinning_order();
+        let mut bench = FirstTouchBench {
+            cases: self.first_touch_cases().into(),
+            config: self,
+            threads: Vec::new(),
+            progress,
+        };
+        for worker in 0..bench.config.threads {
+            bench.spawn_worker(worker, cpus.get(worker % cpus.len().max(1)).copied());
+        }
+        bench
+    }
+}
+
+impl FirstTouchBench {
+    #[must_use]
+    pub fn progress(&self) -> Arc<ProgressTracker<State>> {
+        Arc::clone(&self.progress)
+    }
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        (self.progress.stop_requested() || self.progress.load_state() == State::Done) && {
+            self.threads.iter().all(|t| t.is_finished())
+        }
+    }
+    /// The result of every case, in the order of [`Config::first_touch_cases`]
+    #[must_use]
+    pub fn wait_for_results(self) -> Vec<FirstTouchResult> {
+        let Self { threads, cases, .. } = self;
+        let mut samples = Vec::with_capacity(threads.len());
+        for thread in threads {
+            let Some(thread_samples) = thread.join().unwrap() else {
+                return Vec::new();
+            };
+            samples.push(thread_samples);
+        }
+        cases
+            .iter()
+            .enumerate()
+            .map(|(case_idx, case)| {
+                let case_samples = || samples.iter().map(|thread| &thread[case_idx]);
+                let mut result = FirstTouchResult {
+                    threads: case.threads,
+                    ..FirstTouchResult::default()
+                };
+                for sample in case_samples().flatten() {
+                    result.bytes_touched += sample.bytes;
+                    result.page_faults += sample.page_faults;
+                    if result.backing == AllocatedBacking::default() || sample.backing.fell_back() {
+                        result.backing = sample.backing;
+                    }
+                }
+                // Every round starts at the same barrier, so it lasts from its first start to its
+                // last end
+                let rounds = case_samples().map(Vec::len).max().unwrap_or(0);
+                result.runtime = (0..rounds)
+                    .filter_map(|round| {
+                        let round = || case_samples().filter_map(|thread| thread.get(round));
+                        let start = round().map(|sample| sample.start).min()?;
+                        let end = round().map(|sample| sample.end).max()?;
+                        Some(end - start)
+                    })
+                    .sum();
+                result
+            })
+            .collect()
+    }
+    fn spawn_worker(&mut self, worker: usize, cpu: Option<usize>) {
+        let config = self.config.clone();
+        let cases = Arc::clone(&self.cases);
+        let progress = Arc::clone(&self.progress);
+        self.threads.push(std::thread::spawn(move || {
+            Self::run(config, &cases, worker, cpu, &progress)
+        }));
+    }
+    fn run(
+        mut config: Config,
+        cases: &[FirstTouchCase],
+        worker: usize,
+        cpu: Option<usize>,
+        progress: &Arc<ProgressTracker<State>>,
+    ) -> Option<Vec<Vec<TouchSample>>> {
+        if let Some(cpu) = cpu {
+            pin_current_thread(cpu);
+        }
+        let rounds = config.passes.max(1);
+        let mut samples = Vec::with_capacity(cases.len());
+        // The mapping of the last round, kept until every worker is done touching theirs
+        let mut mapping = None;
+        for (case_idx, case) in cases.iter().enumerate() {
+            config.backing = case.backing;
+            let len = (config.memory_size / case.threads)
+                .max(1)
+                .next_multiple_of(*PAGE_SIZE);
+            let mut case_samples = Vec::with_capacity(rounds);
+            for round in 0..rounds {
+                if case_idx + round > 0 {
+                    // Unmapping takes the same lock as faulting pages in, so wait for every
+                    // worker to finish touching before unmapping the last round's mapping
+                    progress.transition_state(State::Allocating, config.threads as u64);
+                    if progress.stop_requested() {
+                        return None;
+                    }
+                }
+                drop(mapping.take());
+                // Map before the barrier, so that only the faults themselves run concurrently
+                mapping = (worker < case.threads).then(|| allocate_bound_buffer(&config, len));
+                progress.add(1);
+                progress.transition_state(
+                    State::FirstTouch(case_idx * rounds + round + 1, cases.len() * rounds),
+                    (len * case.threads) as u64,
+                );
+                if progress.stop_requested() {
+                    return None;
+                }
+                let Some((memory, _, backing)) = &mut mapping else {
+                    continue;
+                };
+                let faults_before = minor_page_faults();
+                let start = Instant::now();
+                touch_pages(memory, progress)?;
+                let end = Instant::now();
+                case_samples.push(TouchSample {
+                    start,
+                    end,
+                    bytes: memory.len(),
+                    page_faults: minor_page_faults() - faults_before,
+                    backing: *backing,
+                });
+            }
+            samples.push(case_samples);
+        }
+        progress.transition_state(State::Done, config.threads as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+        progress.add(1);
+        Some(samples)
+    }
+}
+
+/// Writes a byte to every page of `memory`, reporting progress every
+/// [`PAGES_PER_PROGRESS_UPDATE`] pages.
+///
+/// Returns `None` as soon as a stop was requested.
+fn touch_pages(memory: &mut [MaybeUninit<u8>], progress: &ProgressTracker<State>) -> Option<()> {
+    for chunk in memory.chunks_mut(*PAGE_SIZE * PAGES_PER_PROGRESS_UPDATE) {
+        for page in chunk.chunks_mut(*PAGE_SIZE) {
+            // SAFETY: The pointer comes from a live mutable reference
+            unsafe { page.as_mut_ptr().write_volatile(MaybeUninit::new(0)) };
+        }
+        progress.add(chunk.len() as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+    }
+    Some(())
+}
+
+/// The minor page faults the calling thread took so far
+fn minor_page_faults() -> u64 {
+    let mut usage = MaybeUninit::<libc::rusage>::uninit();
+    // SAFETY: getrusage fills in the whole struct when it succeeds
+    unsafe {
+        if libc::getrusage(libc::RUSAGE_THREAD, usage.as_mut_ptr()) != 0 {
+            return 0;
+        }
+        usage.assume_init().ru_minflt as u64
+    }
+}
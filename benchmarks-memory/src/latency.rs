@@ -0,0 +1,230 @@
+use crate::{
+    AllocatedBacking, Config, State, allocate_thread_buffer,
+    passes::{PassSchedule, run_passes},
+    pin_current_thread,
+};
+use benchmarks_core::{ProgressTracker, Statistics};
+use rand::{RngExt, SeedableRng};
+use std::{hint::black_box, mem::MaybeUninit, sync::Arc, time::Duration};
+
+/// The distance between two consecutive links of the pointer chain.
+/// Every load is placed on its own cache line.
+pub const CHAIN_STRIDE: usize = 64;
+
+/// The number of loads performed between two progress updates.
+pub(crate) const LOADS_PER_PROGRESS_UPDATE: usize = 4096;
+
+#[derive(Debug)]
+pub struct MemoryLatencyBench {
+    config: Config,
+    schedule: PassSchedule,
+    threads: Vec<std::thread::JoinHandle<Option<LatencyResult>>>,
+    progress: Arc<ProgressTracker<State>>,
+}
+
+impl Config {
+    /// Starts a pointer-chasing latency benchmark with this configuration.
+    /// `operation`, `strategy` and `init_type` are ignored, as the buffer is filled with the
+    /// pointer chain.
+    #[must_use]
+    pub fn start_latency(self) -> MemoryLatencyBench {
+        let progress = Arc::new(ProgressTracker::new(
+            self.threads as u64,
+            self.threads,
+            State::Allocating,
+        ));
+        let cpus = self.pinning_order();
+        let mut bench = MemoryLatencyBench {
+            config: self,
+            schedule: PassSchedule::default(),
+            threads: Vec::new(),
+            progress,
+        };
+        for worker in 0..bench.config.threads {
+            bench.spawn_worker(cpus.get(worker % cpus.len().max(1)).copied());
+        }
+        bench
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LatencyResult {
+    /// The number of dependent loads performed
+    pub loads: usize,
+    pub runtime: Duration,
+    /// The runtime of every measured pass, in order. Every pass performs the same number of loads.
+    pub pass_runtimes: Vec<Duration>,
+    /// The CPU the worker was pinned to, if any
+    pub cpu: Option<usize>,
+    /// The NUMA node the buffer was bound to, if any
+    pub memory_node: Option<usize>,
+    pub backing: AllocatedBacking,
+}
+
+impl LatencyResult {
+    /// The average time it took for a single load to complete, in nanoseconds
+    #[must_use]
+    pub fn ns_per_load(&self) -> f64 {
+        self.runtime.as_secs_f64() * 1e9 / self.loads as f64
+    }
+    /// The average load latency of every measured pass, in nanoseconds
+    pub fn pass_ns_per_load(&self) -> impl Iterator<Item = f64> {
+        let loads_per_pass = self.loads as f64 / self.pass_runtimes.len() as f64;
+        self.pass_runtimes
+            .iter()
+            .map(move |runtime| runtime.as_secs_f64() * 1e9 / loads_per_pass)
+    }
+    /// Statistics over the load latency of every measured pass, in nanoseconds
+    #[must_use]
+    pub fn pass_statistics(&self) -> Option<Statistics> {
+        Statistics::from_samples(self.pass_ns_per_load())
+    }
+}
+
+impl MemoryLatencyBench {
+    #[must_use]
+    pub fn progress(&self) -> Arc<ProgressTracker<State>> {
+        Arc::clone(&self.progress)
+    }
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        (self.progress.stop_requested() || self.progress.load_state() == State::Done) && {
+            self.threads.iter().all(|t| t.is_finished())
+        }
+    }
+    #[must_use]
+    pub fn wait_for_results(self) -> Vec<LatencyResult> {
+        let Self { threads, .. } = self;
+        let mut results = Vec::new();
+        for thread in threads {
+            let Some(sample) = thread.join().unwrap() else {
+                continue;
+            };
+            results.push(sample);
+        }
+        results
+    }
+    fn spawn_worker(&mut self, cpu: Option<usize>) {
+        let config = self.config.clone();
+        let mut schedule = self.schedule.clone();
+        let progress = Arc::clone(&self.progress);
+        self.threads.push(std::thread::spawn(move || {
+            Self::run(&config, cpu, &mut schedule, &progress)
+        }));
+    }
+    fn run(
+        config: &Config,
+        cpu: Option<usize>,
+        schedule: &mut PassSchedule,
+        progress: &Arc<ProgressTracker<State>>,
+    ) -> Option<LatencyResult> {
+        let cpu = cpu.filter(|&cpu| pin_current_thread(cpu));
+        let (mut memory, memory_node, backing) = allocate_thread_buffer(config, progress);
+        let links = memory.len() / CHAIN_STRIDE;
+        progress.transition_state(State::Initializing, (links * config.threads) as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+        let start = build_pointer_chain(&mut memory, progress)?;
+
+        let mut current = start;
+        let pass_runtimes = run_passes(config, progress, schedule, links, |on_loads| {
+            let mut remaining = links;
+            while remaining != 0 {
+                let loads = remaining.min(LOADS_PER_PROGRESS_UPDATE);
+                current = unsafe { chase(current, loads) };
+                remaining -= loads;
+                if !on_loads(loads) {
+                    return None;
+                }
+            }
+            Some(())
+        })?
+        .runtimes;
+        black_box(current);
+        progress.transition_state(State::Done, config.threads as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+        progress.add(1);
+        Some(LatencyResult {
+            loads: links * pass_runtimes.len(),
+            runtime: pass_runtimes.iter().sum(),
+            pass_runtimes,
+            cpu,
+            memory_node,
+            backing,
+        })
+    }
+}
+
+/// Links every [`CHAIN_STRIDE`]-sized slot of `memory` into a single cycle visiting the slots in
+/// a random order, so that hardware prefetchers can't predict the next load.
+///
+/// Returns the address of the first link, or `None` if a stop was requested or `memory` can't hold
+/// a single link.
+pub(crate) fn build_pointer_chain(
+    memory: &mut [MaybeUninit<u8>],
+    progress: &ProgressTracker<State>,
+) -> Option<*const ()> {
+    let links = memory.len() / CHAIN_STRIDE;
+    link_pointer_chain(memory, links, |link| link * CHAIN_STRIDE, progress)
+}
+
+/// Links `links` pointers, the one of every link at byte `offset(link)` of `memory`, into a
+/// single cycle visiting them in a random order. `memory` and every offset have to be
+/// pointer-aligned.
+///
+/// Returns the address of the first link, or `None` if a stop was requested or `links` is 0.
+///
+/// # Panics
+/// If a link doesn't fit into `memory` or isn't aligned
+pub(crate) fn link_pointer_chain(
+    memory: &mut [MaybeUninit<u8>],
+    links: usize,
+    offset: impl Fn(usize) -> usize,
+    progress: &ProgressTracker<State>,
+) -> Option<*const ()> {
+    let base = memory.as_mut_ptr();
+    assert!(
+        (0..links).all(|link| {
+            let link = offset(link);
+            link + size_of::<*const ()>() <= memory.len()
+                && base.wrapping_add(link).cast::<*const ()>().is_aligned()
+        }),
+        "every link has to fit into the buffer and be aligned"
+    );
+    let mut order: Vec<usize> = (0..links).collect();
+    // Sattolo's algorithm, guaranteeing a single cycle through every link
+    let mut rng = rand::rngs::SmallRng::from_rng(&mut rand::rng());
+    for i in (1..links).rev() {
+        order.swap(i, rng.random_range(0..i));
+    }
+    for (idx, chunk) in order.chunks(LOADS_PER_PROGRESS_UPDATE).enumerate() {
+        for (position, &link) in chunk.iter().enumerate() {
+            let next = order[(idx * LOADS_PER_PROGRESS_UPDATE + position + 1) % links];
+            // SAFETY: Every link fits into `memory` and is aligned, as asserted above
+            unsafe {
+                let next = base.add(offset(next)).cast_const().cast::<()>();
+                base.add(offset(link)).cast::<*const ()>().write(next);
+            }
+        }
+        progress.add(chunk.len() as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+    }
+    Some(unsafe { base.add(offset(*order.first()?)).cast() })
+}
+
+/// Follows the pointer chain starting at `current` for `loads` links, returning where it stopped.
+///
+/// # Safety
+/// `current` must point to a link of a chain built by [`build_pointer_chain`] that is still alive.
+#[inline(never)]
+pub(crate) unsafe fn chase(mut current: *const (), loads: usize) -> *const () {
+    for _ in 0..loads {
+        current = unsafe { current.cast::<*const ()>().read() };
+    }
+    current
+}
@@ -1,30 +1,98 @@
 use nix::unistd::SysconfVar;
 use rand::{Rng, SeedableRng};
 use std::{
-    alloc::Layout,
     fmt::Display,
     mem::MaybeUninit,
     ops::{Deref, DerefMut},
-    ptr::NonNull,
     sync::{Arc, LazyLock},
     time::{Duration, Instant},
 };
+mod affinity;
+mod backing;
+mod contention;
+mod copy;
+mod first_touch;
+mod latency;
+mod loaded;
+mod numa;
+mod passes;
+mod pattern;
+mod prefetch;
+mod sharing;
 mod strategies;
 mod strategy_internals;
-use benchmarks_core::{ProgressTracker, SelectableEnum};
+mod stream;
+mod sweep;
+mod tlb;
+mod verify;
+pub use affinity::*;
+pub use backing::*;
+use benchmarks_core::{ProgressTracker, SelectableEnum, Statistics};
+pub use contention::*;
+pub use copy::CopyPlacement;
+use copy::{Destination, DestinationExchange, destination_node};
+pub use first_touch::*;
+pub use latency::*;
+pub use loaded::*;
+pub use numa::*;
+use passes::{MeasuredPasses, PassSchedule, run_passes};
+pub use pattern::*;
+pub use prefetch::*;
+pub use sharing::BufferSharing;
+use sharing::{SharedBuffer, SharedBufferExchange};
 pub use strategies::*;
+pub use strategy_internals::{BlackBoxSink, ReadSink, WRITE_PATTERN};
+use stream::{STREAM_INITIAL_VALUE, stream_array_len, stream_arrays};
+pub use stream::{STREAM_SCALAR, StreamKernelFn};
+pub use sweep::*;
+pub use tlb::*;
+pub use verify::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MemoryOperation {
     Read,
     Write,
     Copy,
+    /// STREAM's `a = q*b`
+    Scale,
+    /// STREAM's `a = b + c`
+    Add,
+    /// STREAM's `a = b + q*c`
+    Triad,
+    /// Reads and writes interleaved a cache line at a time, in `Config::read_write_ratio`
+    Mixed,
+}
+
+impl MemoryOperation {
+    /// Whether this is one of the STREAM kernels, which work on three separate `f64` arrays
+    #[must_use]
+    pub const fn is_stream_kernel(&self) -> bool {
+        matches!(self, Self::Scale | Self::Add | Self::Triad)
+    }
+    /// The number of arrays a STREAM kernel reads or writes, 0 for other operations
+    #[must_use]
+    pub const fn stream_arrays(&self) -> usize {
+        match self {
+            Self::Scale => 2,
+            Self::Add | Self::Triad => 3,
+            Self::Read | Self::Write | Self::Copy | Self::Mixed => 0,
+        }
+    }
+    /// The bytes read plus the bytes written for every byte an operation counts as processed.
+    /// A copy reads and writes every byte it moves, write-allocate traffic is not counted.
+    #[must_use]
+    pub const fn traffic_multiplier(&self) -> usize {
+        match self {
+            Self::Copy => 2,
+            _ => 1,
+        }
+    }
 }
 
 impl SelectableEnum for MemoryOperation {
     fn all_values() -> &'static [Self] {
         use MemoryOperation::*;
-        &[Read, Write, Copy]
+        &[Read, Write, Copy, Scale, Add, Triad, Mixed]
     }
     fn as_str(&self) -> &'static str {
         use MemoryOperation::*;
@@ -32,6 +100,10 @@ impl SelectableEnum for MemoryOperation {
             Read => "Read",
             Write => "Write",
             Copy => "Copy",
+            Scale => "Scale",
+            Add => "Add",
+            Triad => "Triad",
+            Mixed => "Mixed",
         }
     }
 }
@@ -62,7 +134,26 @@ impl SelectableEnum for MemoryInitializationType {
 pub enum State {
     Allocating,
     Initializing,
+    WarmingUp(usize, usize),
+    /// The current pass and the total number of passes, 0 when running for a fixed duration
     Executing(usize, usize),
+    Sweeping(usize, usize),
+    /// The current prefetch distance of a prefetch sweep and the total number of them
+    PrefetchDistance(usize, usize),
+    NodePair(usize, usize),
+    /// The current injection delay of a loaded-latency benchmark and the total number of them
+    InjectionDelay(usize, usize),
+    /// The current test and the total number of tests, counting every repetition
+    Verifying(usize, usize),
+    /// The current round of a first-touch benchmark and the total number of them, counting the
+    /// rounds of every case
+    FirstTouch(usize, usize),
+    /// The current layout and thread count of a contention benchmark and the total number of
+    /// them
+    Contention(usize, usize),
+    /// The current page count of a TLB reach benchmark and the total number of them, counting
+    /// those of every page size
+    TlbReach(usize, usize),
     Done,
 }
 
@@ -72,26 +163,119 @@ impl Display for State {
         let text = match self {
             Allocating => "Allocating Buffers",
             Initializing => "Initializing Buffers",
+            WarmingUp(pass, total) => return write!(f, "Warm-up pass {pass} of {total}"),
+            Executing(pass, 0) => return write!(f, "Pass {pass}"),
             Executing(pass, total) => return write!(f, "Pass {pass} of {total}"),
+            Sweeping(step, total) => return write!(f, "Size {step} of {total}"),
+            PrefetchDistance(step, total) => return write!(f, "Distance {step} of {total}"),
+            NodePair(pair, total) => return write!(f, "Node pair {pair} of {total}"),
+            InjectionDelay(step, total) => {
+                return write!(f, "Injection delay {step} of {total}");
+            }
+            Verifying(test, total) => return write!(f, "Test {test} of {total}"),
+            FirstTouch(round, total) => return write!(f, "Round {round} of {total}"),
+            Contention(step, total) => return write!(f, "Step {step} of {total}"),
+            TlbReach(step, total) => return write!(f, "Page count {step} of {total}"),
             Done => "Done",
         };
         f.write_str(text)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Config {
     pub memory_size: usize,
     pub passes: usize,
+    /// Passes that run before the measured ones without being measured, to warm up the caches
+    /// and fault in the buffers
+    pub warmup_passes: usize,
+    /// When set, measured passes repeat until this much time has passed and `passes` is ignored
+    pub run_duration: Option<Duration>,
     pub threads: usize,
     pub operation: MemoryOperation,
     pub init_type: MemoryInitializationType,
     pub strategy: OperationStrategy,
+    /// Whether the SIMD strategies use streaming loads and stores that bypass the caches
+    /// instead of regular ones, see [`OperationStrategy::supports_non_temporal`]
+    pub non_temporal: bool,
+    /// The cache level the prefetching strategies prefetch into, see
+    /// [`OperationStrategy::is_prefetching`]
+    pub prefetch_hint: PrefetchHint,
+    /// How far ahead of every cache line they read the prefetching strategies prefetch, in bytes
+    pub prefetch_distance: usize,
+    /// The bytes every call into `strategy` processes at once, which is also how often passes
+    /// report their progress. Passes that go a cache line at a time only report progress this
+    /// often.
+    pub block_size: usize,
+    /// How many bytes past a page boundary the buffers passes work on start at, to measure the
+    /// cost of unaligned and cache line splitting accesses. Buffers get shorter by as much.
+    /// When set, reads, writes and copies use the unaligned variants of `strategy`, see
+    /// [`OperationStrategy::unaligned_read_fn`]. The prefetching strategies and the STREAM
+    /// kernels realign to whole cache lines and `f64`s respectively.
+    pub misalignment: usize,
+    /// The order reads, writes and mixed passes visit memory in. Copies and the STREAM kernels
+    /// are always sequential.
+    pub access_pattern: AccessPattern,
+    /// The distance between two consecutive accesses of [`AccessPattern::Strided`], in bytes.
+    /// Rounded down to whole cache lines.
+    pub stride: usize,
+    /// The seed of the permutation used by [`AccessPattern::Random`]
+    pub pattern_seed: u64,
+    pub pinning: PinningPolicy,
+    /// The CPUs used by [`PinningPolicy::Explicit`]
+    pub pinned_cpus: Vec<usize>,
+    /// The NUMA node every buffer gets bound to, or `None` to leave placement to first-touch
+    pub memory_node: Option<usize>,
+    pub backing: BufferBacking,
+    /// Where [`MemoryOperation::Copy`] copies to. Every worker has its own source and
+    /// destination buffer, each of `memory_size / threads` bytes.
+    pub copy_placement: CopyPlacement,
+    /// The cache lines [`MemoryOperation::Mixed`] reads for every cache line it writes
+    pub read_write_ratio: ReadWriteRatio,
+    /// Whether the workers of reads, writes and mixed passes share a single buffer of
+    /// `memory_size` bytes instead of each allocating its own, see [`Config::shares_buffer`]
+    pub buffer_sharing: BufferSharing,
+    /// The operation the threads of [`Config::start_contention`] increment their counters with
+    pub atomic_operation: AtomicOperation,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            memory_size: *PAGE_SIZE * 1024 * 10,
+            passes: 2,
+            warmup_passes: 0,
+            run_duration: None,
+            threads: 1,
+            operation: MemoryOperation::Read,
+            init_type: MemoryInitializationType::Zeros,
+            strategy: OperationStrategy::Bytewise,
+            non_temporal: false,
+            prefetch_hint: PrefetchHint::T0,
+            prefetch_distance: DEFAULT_PREFETCH_DISTANCE,
+            block_size: *PAGE_SIZE * 4,
+            misalignment: 0,
+            access_pattern: AccessPattern::Sequential,
+            stride: 4096,
+            pattern_seed: 0,
+            pinning: PinningPolicy::None,
+            pinned_cpus: Vec::new(),
+            memory_node: None,
+            backing: BufferBacking::Heap,
+            copy_placement: CopyPlacement::SameThread,
+            read_write_ratio: ReadWriteRatio::default(),
+            buffer_sharing: BufferSharing::Private,
+            atomic_operation: AtomicOperation::FetchAdd,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct MemoryThroughputBench {
     config: Config,
+    schedule: PassSchedule,
+    destinations: DestinationExchange,
+    shared_buffer: SharedBufferExchange,
     threads: Vec<std::thread::JoinHandle<Option<TestResult>>>,
     progress: Arc<ProgressTracker<State>>,
 }
@@ -104,13 +288,17 @@ impl Config {
             self.threads,
             State::Allocating,
         ));
+        let cpus = self.pinning_order();
         let mut bench = MemoryThroughputBench {
             config: self,
+            schedule: PassSchedule::default(),
+            destinations: DestinationExchange::default(),
+            shared_buffer: SharedBufferExchange::default(),
             threads: Vec::new(),
             progress,
         };
-        for _ in 0..bench.config.threads {
-            bench.spawn_worker();
+        for worker in 0..bench.config.threads {
+            bench.spawn_worker(worker, cpus.get(worker % cpus.len().max(1)).copied());
         }
         bench
     }
@@ -123,16 +311,89 @@ impl Config {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct TestResult {
+    /// The bytes the operation processed, e.g. the bytes moved by a copy
     pub memory_processed: usize,
+    /// The bytes read plus the bytes written while processing `memory_processed`
+    pub memory_traffic: usize,
+    /// The bytes of `memory_traffic` that were written, the rest were read
+    pub bytes_written: usize,
     pub runtime: Duration,
+    /// The runtime of every measured pass, in order. Every pass processes the same amount of
+    /// memory.
+    pub pass_runtimes: Vec<Duration>,
+    /// When the measured region started, `None` if nothing was measured.
+    /// Combined results span from the earliest start to the latest end of all threads.
+    pub measure_start: Option<Instant>,
+    /// When the measured region ended
+    pub measure_end: Option<Instant>,
+    /// The CPU the worker was pinned to, if any
+    pub cpu: Option<usize>,
+    /// The NUMA node the buffer was bound to, if any
+    pub memory_node: Option<usize>,
+    /// The NUMA node the destination of a copy was bound to, if any
+    pub destination_node: Option<usize>,
+    pub backing: AllocatedBacking,
 }
 impl TestResult {
     #[must_use]
     pub fn throughput(&self) -> f64 {
         self.memory_processed as f64 / self.runtime.as_secs_f64()
     }
+    /// The throughput over the wall-clock window of the measured region, in bytes per second.
+    /// Unlike [`TestResult::throughput`] this includes the time workers spent waiting for each
+    /// other, which makes it the true aggregate bandwidth of combined results.
+    #[must_use]
+    pub fn wall_clock_throughput(&self) -> f64 {
+        match (self.measure_start, self.measure_end) {
+            (Some(start), Some(end)) => self.memory_processed as f64 / (end - start).as_secs_f64(),
+            _ => self.throughput(),
+        }
+    }
+    /// The read plus write traffic over the wall-clock window of the measured region, in bytes
+    /// per second
+    #[must_use]
+    pub fn wall_clock_traffic(&self) -> f64 {
+        self.wall_clock_throughput() * self.memory_traffic as f64 / self.memory_processed as f64
+    }
+    /// The bytes read for every byte written, infinite if nothing was written
+    #[must_use]
+    pub fn read_write_ratio(&self) -> f64 {
+        (self.memory_traffic - self.bytes_written) as f64 / self.bytes_written as f64
+    }
+    /// The throughput of every measured pass, in bytes per second
+    pub fn pass_throughputs(&self) -> impl Iterator<Item = f64> {
+        let bytes_per_pass = self.memory_processed as f64 / self.pass_runtimes.len() as f64;
+        self.pass_runtimes
+            .iter()
+            .map(move |runtime| bytes_per_pass / runtime.as_secs_f64())
+    }
+    /// Statistics over the throughput of every measured pass, in bytes per second
+    #[must_use]
+    pub fn pass_statistics(&self) -> Option<Statistics> {
+        Statistics::from_samples(self.pass_throughputs())
+    }
+    /// Merges in the result of a thread that ran concurrently with this one, treating the slowest
+    /// of the two as the runtime of both, pass by pass, and widening the measured region to cover
+    /// both. Keeps the backing of `other` if it had to fall back.
+    pub fn combine_parallel(&mut self, other: &TestResult) {
+        self.memory_processed += other.memory_processed;
+        self.memory_traffic += other.memory_traffic;
+        self.bytes_written += other.bytes_written;
+        self.runtime = self.runtime.max(other.runtime);
+        self.measure_start = match (self.measure_start, other.measure_start) {
+            (Some(start), Some(other)) => Some(start.min(other)),
+            (start, other) => start.or(other),
+        };
+        self.measure_end = self.measure_end.max(other.measure_end);
+        for (runtime, other) in self.pass_runtimes.iter_mut().zip(&other.pass_runtimes) {
+            *runtime = (*runtime).max(*other);
+        }
+        if other.backing.fell_back() {
+            self.backing = other.backing;
+        }
+    }
 }
 pub static PAGE_SIZE: LazyLock<usize> = LazyLock::new(|| {
     nix::unistd::sysconf(SysconfVar::PAGE_SIZE)
@@ -143,13 +404,13 @@ pub static PAGE_SIZE: LazyLock<usize> = LazyLock::new(|| {
 
 struct OwnedPtr<T: ?Sized> {
     ptr: *mut T,
-    layout: Layout,
+    allocation: Allocation,
 }
 
 impl<T: ?Sized> Drop for OwnedPtr<T> {
     fn drop(&mut self) {
         unsafe {
-            std::alloc::dealloc(self.ptr.cast(), self.layout);
+            self.allocation.release();
         }
     }
 }
@@ -190,130 +451,425 @@ impl MemoryThroughputBench {
         }
         results
     }
-    fn spawn_worker(&mut self) {
+    fn spawn_worker(&mut self, worker: usize, cpu: Option<usize>) {
         let config = self.config.clone();
+        let mut schedule = self.schedule.clone();
+        let destinations = self.destinations.clone();
+        let shared_buffer = self.shared_buffer.clone();
         let progress = Arc::clone(&self.progress);
-        self.threads
-            .push(std::thread::spawn(move || Self::run(&config, &progress)));
-    }
-    fn run(config: &Config, progress: &Arc<ProgressTracker<State>>) -> Option<TestResult> {
-        let chunk_size = *PAGE_SIZE * 4;
-        let mem = config.thread_memory_layout();
-        let mut memory = unsafe {
-            let ptr = std::alloc::alloc(mem);
-            let Some(ptr) = NonNull::new(ptr) else {
-                std::alloc::handle_alloc_error(mem);
-            };
-            let ptr: *mut [MaybeUninit<u8>] =
-                std::ptr::slice_from_raw_parts_mut(ptr.as_ptr().cast(), mem.size());
-            OwnedPtr { ptr, layout: mem }
+        self.threads.push(std::thread::spawn(move || {
+            Self::run(
+                &config,
+                worker,
+                cpu,
+                &mut schedule,
+                &destinations,
+                &shared_buffer,
+                &progress,
+            )
+        }));
+    }
+    fn run(
+        config: &Config,
+        worker: usize,
+        cpu: Option<usize>,
+        schedule: &mut PassSchedule,
+        destinations: &DestinationExchange,
+        shared_buffer: &SharedBufferExchange,
+        progress: &Arc<ProgressTracker<State>>,
+    ) -> Option<TestResult> {
+        let cpu = cpu.filter(|&cpu| pin_current_thread(cpu));
+        let chunk_size = config.block_size.max(1);
+        let mut private_memory;
+        let shared_memory;
+        let (memory, memory_node, backing): (*mut [u8], _, _) = if config.shares_buffer() {
+            if worker == 0 {
+                shared_buffer.publish(Arc::new(SharedBuffer::allocate(config)));
+            }
+            progress.add(1);
+            let len = SharedBuffer::len(config);
+            progress.transition_state(State::Initializing, len as u64);
+            if progress.stop_requested() {
+                return None;
+            }
+            // The first worker published the buffer before the transition to initializing
+            shared_memory = shared_buffer.get();
+            if worker == 0 {
+                // SAFETY: No other worker touches the buffer before the first pass
+                let memory = unsafe { &mut *(shared_memory.as_ptr() as *mut [MaybeUninit<u8>]) };
+                fill_buffer(config, progress, memory)?;
+            }
+            (
+                shared_memory.as_ptr(),
+                shared_memory.node,
+                shared_memory.backing,
+            )
+        } else {
+            let (memory, memory_node, backing) = allocate_thread_buffer(config, progress);
+            if config.operation == MemoryOperation::Copy {
+                let node = destination_node(config, memory_node);
+                let destination = Destination::allocate(config, memory.len(), node);
+                destinations.publish(worker, Arc::new(destination));
+            }
+            private_memory = initialize_thread_buffer(config, progress, memory)?;
+            (&raw mut *private_memory, memory_node, backing)
         };
-        progress.add(1);
-        progress.transition_state(State::Initializing, (mem.size() * config.threads) as u64);
+        let memory = misaligned(config, memory);
+        // Every worker published its destination before the transition to initializing
+        let destination = (config.operation == MemoryOperation::Copy)
+            .then(|| destinations.destination_of(config, worker));
+        let order = if config.shares_buffer() && config.buffer_sharing == BufferSharing::Interleaved
+        {
+            LineOrder::interleaved(config, memory.len(), worker)
+        } else {
+            LineOrder::new(config, memory.len())
+        };
+        let pass_bytes = bytes_per_pass(config, order.pass_len(memory.len()));
+        // Every pass writes the same bytes
+        let mut pass_bytes_written = 0;
+        let MeasuredPasses {
+            runtimes: pass_runtimes,
+            work: memory_processed,
+            start: measure_start,
+            end: measure_end,
+        } = run_passes(config, progress, schedule, pass_bytes, |on_chunk| {
+            let destination = destination.as_ref().map(|d| misaligned(config, d.as_ptr()));
+            pass_bytes_written = 0;
+            // SAFETY: The buffer stays alive until the worker returns, and a shared buffer was
+            // filled by the first worker before the transition to the first pass. Only this
+            // worker copies into its destination, and its owner no longer touches it.
+            unsafe {
+                execute_pass(
+                    config,
+                    memory,
+                    destination,
+                    &order,
+                    chunk_size,
+                    &mut pass_bytes_written,
+                    on_chunk,
+                )
+            }
+        })?;
+        progress.transition_state(State::Done, config.threads as u64);
         if progress.stop_requested() {
             return None;
         }
-        match config.init_type {
-            // The data was zeroed on initialization
-            MemoryInitializationType::Zeros => {
-                for chunk in memory.chunks_exact_mut(chunk_size) {
-                    for b in chunk.iter_mut() {
-                        b.write(0);
-                    }
-                    progress.add(chunk.len() as u64);
-                    if progress.stop_requested() {
-                        return None;
-                    }
+        progress.add(1);
+        Some(TestResult {
+            memory_processed,
+            memory_traffic: memory_processed * config.operation.traffic_multiplier(),
+            bytes_written: pass_bytes_written * pass_runtimes.len(),
+            runtime: pass_runtimes.iter().sum(),
+            pass_runtimes,
+            measure_start,
+            measure_end,
+            cpu,
+            memory_node,
+            destination_node: destination.and_then(|d| d.node),
+            backing,
+        })
+    }
+}
+
+/// Runs `config.operation` once over every `chunk_size` chunk of `memory`, calling `on_chunk` with
+/// the number of bytes the strategy processed in each chunk. Reads and writes visit the cache lines
+/// of `memory` in `order` unless it is sequential, reporting progress every `chunk_size` bytes,
+/// followed by the bytes after the last whole cache line. Mixed passes always go a cache line at a
+/// time. Copies go from `memory` to `destination`, which must be at least as long.
+///
+/// Adds the number of bytes written to `bytes_written` as it goes, including those of chunks
+/// after which the pass got stopped.
+///
+/// Returns `None` as soon as `on_chunk` returns `false`.
+///
+/// # Safety
+/// `memory` must be valid for reads and writes. Reads, writes and mixed passes only access it
+/// through the pointer, so other workers may race on it, see [`BufferSharing`]. Copies and the
+/// STREAM kernels need it to themselves, and copies need `destination` to themselves as well.
+unsafe fn execute_pass(
+    config: &Config,
+    memory: *mut [u8],
+    destination: Option<*mut [u8]>,
+    order: &LineOrder,
+    chunk_size: usize,
+    bytes_written: &mut usize,
+    mut on_chunk: impl FnMut(usize) -> bool,
+) -> Option<()> {
+    let (data, len) = (memory.cast::<u8>(), memory.len());
+    match config.operation {
+        MemoryOperation::Read | MemoryOperation::Write
+            if !matches!(order, LineOrder::Sequential) =>
+        unsafe {
+            execute_line_pass(config, memory, order, chunk_size, bytes_written, on_chunk)?;
+        },
+        MemoryOperation::Mixed => unsafe {
+            execute_line_pass(config, memory, order, chunk_size, bytes_written, on_chunk)?;
+        },
+        MemoryOperation::Read => {
+            let work_read_fn = config.read_fn();
+            for start in (0..len).step_by(chunk_size) {
+                let chunk_len = chunk_size.min(len - start);
+                if !on_chunk(work_read_fn(unsafe { data.add(start) }, chunk_len)) {
+                    return None;
                 }
             }
-            MemoryInitializationType::Ones => {
-                for chunk in memory.chunks_exact_mut(chunk_size) {
-                    for b in chunk.iter_mut() {
-                        b.write(u8::MAX);
-                    }
-                    progress.add(chunk.len() as u64);
-                    if progress.stop_requested() {
-                        return None;
-                    }
+        }
+        MemoryOperation::Write => {
+            let work_write_fn = config.write_fn();
+            for start in (0..len).step_by(chunk_size) {
+                let chunk_len = chunk_size.min(len - start);
+                let written = unsafe { work_write_fn(data.add(start), chunk_len) };
+                *bytes_written += written;
+                if !on_chunk(written) {
+                    return None;
                 }
             }
-            MemoryInitializationType::Random => {
-                let mut rng = rand::rngs::SmallRng::from_rng(&mut rand::rng());
-                for chunk in memory.chunks_exact_mut(chunk_size) {
-                    for c in chunk.chunks_mut(8) {
-                        let bytes = rng.next_u64().to_ne_bytes();
-                        unsafe {
-                            c.as_mut_ptr()
-                                .copy_from_nonoverlapping(bytes.as_ptr().cast(), c.len());
-                        }
-                    }
-                    progress.add(chunk.len() as u64);
-                    if progress.stop_requested() {
-                        return None;
-                    }
+        }
+        MemoryOperation::Copy => {
+            // SAFETY: Copies never share their buffers, see `Config::shares_buffer`
+            let memory = unsafe { &*memory };
+            let destination =
+                unsafe { &mut *destination.expect("copies need a destination buffer") };
+            let work_copy_fn = config.copy_fn();
+            let prefetching_copy_fn = config.strategy.prefetching_copy_fn(config.prefetch_hint);
+            for (from, to) in memory
+                .chunks(chunk_size)
+                .zip(destination.chunks_mut(chunk_size))
+            {
+                let (from, to, len) = (from.as_ptr(), to.as_mut_ptr(), from.len());
+                let copied = match prefetching_copy_fn {
+                    Some(copy) => unsafe { copy(from, to, len, config.prefetch_distance) },
+                    None => unsafe { work_copy_fn(from, to, len) },
+                };
+                *bytes_written += copied;
+                if !on_chunk(copied) {
+                    return None;
                 }
             }
         }
-        // SAFETY: At this point the memory must have been initialized
-        let mut memory: OwnedPtr<[u8]> = unsafe { core::mem::transmute(memory) };
-        let mut total_runtime = Duration::ZERO;
-        let work_read_fn = config.strategy.read_fn();
-        let work_write_fn = config.strategy.write_fn();
-        let work_copy_fn = config.strategy.copy_nonoverlapping_fn();
-        for pass in 0..config.passes {
-            progress.transition_state(
-                State::Executing(pass + 1, config.passes),
-                (mem.size() * config.threads) as u64,
-            );
-            if progress.stop_requested() {
+        MemoryOperation::Scale | MemoryOperation::Add | MemoryOperation::Triad => {
+            // SAFETY: The STREAM kernels never share their buffer, see `Config::shares_buffer`
+            let memory = unsafe { &mut *memory };
+            let kernel = config
+                .strategy
+                .stream_kernel_fn(config.operation, config.uses_non_temporal());
+            let arrays = config.operation.stream_arrays();
+            let (a, b, c) = stream_arrays(memory);
+            // Whole cache lines, keeping every chunk aligned for streaming stores
+            let elements_per_chunk = (chunk_size / size_of::<f64>() / arrays)
+                .max(1)
+                .next_multiple_of(CACHE_LINE / size_of::<f64>());
+            for start in (0..a.len()).step_by(elements_per_chunk) {
+                let end = (start + elements_per_chunk).min(a.len());
+                unsafe {
+                    kernel(
+                        &mut a[start..end],
+                        &b[start..end],
+                        &c[start..end],
+                        STREAM_SCALAR,
+                    );
+                }
+                *bytes_written += (end - start) * size_of::<f64>();
+                if !on_chunk((end - start) * arrays * size_of::<f64>()) {
+                    return None;
+                }
+            }
+        }
+    }
+    Some(())
+}
+
+/// Runs a read, write or mixed pass over `memory` a cache line at a time, see [`execute_pass`].
+/// Mixed passes decide whether to read or write every line by the order it is visited in.
+///
+/// # Safety
+/// `memory` must be valid for reads and writes, see [`execute_pass`]
+unsafe fn execute_line_pass(
+    config: &Config,
+    memory: *mut [u8],
+    order: &LineOrder,
+    chunk_size: usize,
+    bytes_written: &mut usize,
+    mut on_chunk: impl FnMut(usize) -> bool,
+) -> Option<()> {
+    let read_fn = config.read_fn();
+    let write_fn = config.write_fn();
+    let mut lines_visited = 0;
+    let mut work_fn = |line: *mut u8, len: usize| {
+        let write = match config.operation {
+            MemoryOperation::Write => true,
+            MemoryOperation::Mixed => config.read_write_ratio.is_write(lines_visited),
+            _ => false,
+        };
+        lines_visited += 1;
+        if write {
+            let written = unsafe { write_fn(line, len) };
+            *bytes_written += written;
+            written
+        } else {
+            read_fn(line, len)
+        }
+    };
+    let data = memory.cast::<u8>();
+    let lines = memory.len() / CACHE_LINE;
+    let line = |line: usize| unsafe { data.add(line * CACHE_LINE) };
+    let mut processed = 0;
+    if let LineOrder::Sequential = order {
+        for idx in 0..lines {
+            processed += work_fn(line(idx), CACHE_LINE);
+            if processed >= chunk_size && !on_chunk(std::mem::take(&mut processed)) {
                 return None;
             }
+        }
+    } else {
+        order.for_each_line(|idx| {
+            processed += work_fn(line(idx), CACHE_LINE);
+            processed < chunk_size || on_chunk(std::mem::take(&mut processed))
+        })?;
+    }
+    // The bytes after the last whole cache line go last
+    if order.visits_tail() {
+        processed += work_fn(line(lines), memory.len() - lines * CACHE_LINE);
+    }
+    if processed != 0 && !on_chunk(processed) {
+        return None;
+    }
+    Some(())
+}
+
+/// Skips the first `config.misalignment` bytes of `memory`, see `Config::misalignment`
+fn misaligned(config: &Config, memory: *mut [u8]) -> *mut [u8] {
+    let offset = config.misalignment.min(memory.len());
+    std::ptr::slice_from_raw_parts_mut(
+        memory.cast::<u8>().wrapping_add(offset),
+        memory.len() - offset,
+    )
+}
+
+/// The bytes a single pass of `config.operation` over a buffer of `len` bytes processes.
+/// The STREAM kernels count every array element read or written once, as STREAM does, so Scale
+/// moves 16 bytes per element and Add and Triad move 24.
+fn bytes_per_pass(config: &Config, len: usize) -> usize {
+    match config.operation {
+        MemoryOperation::Read
+        | MemoryOperation::Write
+        | MemoryOperation::Copy
+        | MemoryOperation::Mixed => len,
+        MemoryOperation::Scale | MemoryOperation::Add | MemoryOperation::Triad => {
+            stream_array_len(len) * config.operation.stream_arrays() * size_of::<f64>()
+        }
+    }
+}
+
+/// Allocates the calling worker's buffer with `config.backing`, binding it to
+/// `config.memory_node` if possible. Leaves `progress` in [`State::Allocating`].
+///
+/// Returns the buffer, the node it was bound to and the backing it ended up with.
+fn allocate_thread_buffer(
+    config: &Config,
+    progress: &ProgressTracker<State>,
+) -> (OwnedPtr<[MaybeUninit<u8>]>, Option<usize>, AllocatedBacking) {
+    let buffer = allocate_bound_buffer(config, config.thread_memory_layout().size());
+    progress.add(1);
+    buffer
+}
 
-            let start = Instant::now();
+/// Allocates a buffer of `len` bytes with `config.backing`, binding it to `config.memory_node`
+/// if possible, see [`allocate_thread_buffer`]
+fn allocate_bound_buffer(
+    config: &Config,
+    len: usize,
+) -> (OwnedPtr<[MaybeUninit<u8>]>, Option<usize>, AllocatedBacking) {
+    let (mut memory, backing) = allocate_buffer(len, config.backing);
+    // Bind the whole allocation, hugetlbfs mappings can't be split at arbitrary offsets
+    let memory_node = config
+        .memory_node
+        .filter(|&node| bind_to_node(memory.allocation.as_uninit_slice(), node));
+    (memory, memory_node, backing)
+}
+
+/// Transitions `progress` to [`State::Initializing`] and fills `memory` according to
+/// `config.init_type`.
+///
+/// Returns `None` if a stop was requested in the meantime.
+fn initialize_thread_buffer(
+    config: &Config,
+    progress: &ProgressTracker<State>,
+    mut memory: OwnedPtr<[MaybeUninit<u8>]>,
+) -> Option<OwnedPtr<[u8]>> {
+    progress.transition_state(State::Initializing, (memory.len() * config.threads) as u64);
+    if progress.stop_requested() {
+        return None;
+    }
+    fill_buffer(config, progress, &mut memory)?;
+    // SAFETY: At this point the memory must have been initialized
+    Some(unsafe { core::mem::transmute::<OwnedPtr<[MaybeUninit<u8>]>, OwnedPtr<[u8]>>(memory) })
+}
 
-            match config.operation {
-                MemoryOperation::Read => {
-                    for chunk in memory.chunks_exact_mut(chunk_size) {
-                        work_read_fn(chunk);
-                        progress.add(chunk.len() as u64);
-                        if progress.stop_requested() {
-                            return None;
-                        }
+/// Fills every byte of `memory` according to `config.init_type`, adding them to `progress`.
+///
+/// Returns `None` if a stop was requested in the meantime.
+fn fill_buffer(
+    config: &Config,
+    progress: &ProgressTracker<State>,
+    memory: &mut [MaybeUninit<u8>],
+) -> Option<()> {
+    let chunk_size = *PAGE_SIZE * 4;
+    match config.init_type {
+        // Arbitrary bytes can be denormal floats, which would slow the STREAM kernels down
+        _ if config.operation.is_stream_kernel() => {
+            let value = STREAM_INITIAL_VALUE.to_ne_bytes();
+            for chunk in memory.chunks_mut(chunk_size) {
+                for word in chunk.chunks_mut(value.len()) {
+                    for (b, &value) in word.iter_mut().zip(&value) {
+                        b.write(value);
                     }
                 }
-                MemoryOperation::Write => {
-                    for chunk in memory.chunks_exact_mut(chunk_size) {
-                        work_write_fn(chunk);
-                        progress.add(chunk.len() as u64);
-                        if progress.stop_requested() {
-                            return None;
-                        }
-                    }
+                progress.add(chunk.len() as u64);
+                if progress.stop_requested() {
+                    return None;
                 }
-                MemoryOperation::Copy => {
-                    for chunk in memory.chunks_exact_mut(chunk_size) {
-                        let (from, to) = chunk.split_at_mut(chunk.len() / 2);
-                        unsafe {
-                            work_copy_fn(from.as_ptr(), to.as_mut_ptr(), from.len());
-                        }
-                        progress.add(chunk.len() as u64);
-                        if progress.stop_requested() {
-                            return None;
-                        }
-                    }
+            }
+        }
+        // The data was zeroed on initialization
+        MemoryInitializationType::Zeros => {
+            for chunk in memory.chunks_mut(chunk_size) {
+                for b in chunk.iter_mut() {
+                    b.write(0);
+                }
+                progress.add(chunk.len() as u64);
+                if progress.stop_requested() {
+                    return None;
                 }
             }
-            let pass_runtime = start.elapsed();
-            total_runtime += pass_runtime;
         }
-        progress.transition_state(State::Done, config.threads as u64);
-        if progress.stop_requested() {
-            return None;
+        MemoryInitializationType::Ones => {
+            for chunk in memory.chunks_mut(chunk_size) {
+                for b in chunk.iter_mut() {
+                    b.write(u8::MAX);
+                }
+                progress.add(chunk.len() as u64);
+                if progress.stop_requested() {
+                    return None;
+                }
+            }
+        }
+        MemoryInitializationType::Random => {
+            let mut rng = rand::rngs::SmallRng::from_rng(&mut rand::rng());
+            for chunk in memory.chunks_mut(chunk_size) {
+                for c in chunk.chunks_mut(8) {
+                    let bytes = rng.next_u64().to_ne_bytes();
+                    unsafe {
+                        c.as_mut_ptr()
+                            .copy_from_nonoverlapping(bytes.as_ptr().cast(), c.len());
+                    }
+                }
+                progress.add(chunk.len() as u64);
+                if progress.stop_requested() {
+                    return None;
+                }
+            }
         }
-        progress.add(1);
-        Some(TestResult {
-            memory_processed: memory.len() * config.passes,
-            runtime: total_runtime,
-        })
     }
+    Some(())
 }
@@ -0,0 +1,294 @@
+use crate::{
+    CACHE_LINE, Config, Destination, LatencyResult, LineOrder, MemoryOperation, State, TestResult,
+    allocate_thread_buffer, execute_pass, initialize_thread_buffer,
+    latency::{LOADS_PER_PROGRESS_UPDATE, build_pointer_chain, chase},
+    misaligned, pin_current_thread,
+};
+use benchmarks_core::ProgressTracker;
+use std::{
+    hint::black_box,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// The injection delays of a loaded-latency curve, from full bandwidth pressure down to almost
+/// none. Every unit is a single iteration of an empty loop between two cache lines, roughly a
+/// CPU cycle.
+pub const DEFAULT_INJECTION_DELAYS: &[usize] = &[
+    0, 25, 50, 100, 200, 400, 800, 1600, 3200, 6400, 12800, 25600,
+];
+
+/// How long every injection delay is measured for unless `Config::run_duration` is set
+const DEFAULT_STEP_DURATION: Duration = Duration::from_secs(1);
+
+/// The latency and bandwidth measured at a single injection delay
+#[derive(Debug, Clone)]
+pub struct LoadedLatencyPoint {
+    /// The empty loop iterations every bandwidth thread waited between two cache lines
+    pub injection_delay: usize,
+    /// The latency of the pointer chase
+    pub latency: LatencyResult,
+    /// The bandwidth generated by all other threads together
+    pub bandwidth: TestResult,
+}
+
+/// The samples of a single worker, one for every injection delay
+#[derive(Debug)]
+enum WorkerSamples {
+    Latency(Vec<LatencyResult>),
+    Bandwidth(Vec<TestResult>),
+}
+
+#[derive(Debug)]
+pub struct LoadedLatencyBench {
+    config: Config,
+    delays: Arc<[usize]>,
+    /// The number of injection delays the latency thread finished measuring
+    finished_steps: Arc<AtomicUsize>,
+    threads: Vec<std::thread::JoinHandle<Option<WorkerSamples>>>,
+    progress: Arc<ProgressTracker<State>>,
+}
+
+impl Config {
+    /// Starts a loaded-latency benchmark. The first thread chases pointers through its buffer
+    /// like [`Config::start_latency`] while the other `threads - 1` run `operation` with
+    /// `strategy`, waiting for every delay of `delays` between two cache lines in turn.
+    ///
+    /// Every delay is measured for `run_duration`, or a second if it isn't set. `passes` and
+    /// `warmup_passes` are ignored, and copies always go to a buffer of the same thread.
+    ///
+    /// # Panics
+    /// If `threads` is less than 2, as there would be nothing generating bandwidth
+    #[must_use]
+    pub fn start_loaded_latency(self, delays: &[usize]) -> LoadedLatencyBench {
+        assert!(
+            self.threads >= 2,
+            "loaded latency needs a thread generating bandwidth next to the latency thread"
+        );
+        let progress = Arc::new(ProgressTracker::new(
+            self.threads as u64,
+            self.threads,
+            State::Allocating,
+        ));
+        let cpus = self.pinning_order();
+        let mut bench = LoadedLatencyBench {
+            config: self,
+            delays: delays.into(),
+            finished_steps: Arc::default(),
+            threads: Vec::new(),
+            progress,
+        };
+        for worker in 0..bench.config.threads {
+            bench.spawn_worker(worker, cpus.get(worker % cpus.len().max(1)).copied());
+        }
+        bench
+    }
+}
+
+impl LoadedLatencyBench {
+    #[must_use]
+    pub fn progress(&self) -> Arc<ProgressTracker<State>> {
+        Arc::clone(&self.progress)
+    }
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        (self.progress.stop_requested() || self.progress.load_state() == State::Done) && {
+            self.threads.iter().all(|t| t.is_finished())
+        }
+    }
+    /// Combines the samples of all threads into a latency-vs-bandwidth curve, in the order of
+    /// the injection delays
+    #[must_use]
+    pub fn wait_for_results(self) -> Vec<LoadedLatencyPoint> {
+        let Self {
+            threads, delays, ..
+        } = self;
+        let mut latencies = Vec::new();
+        let mut bandwidths: Vec<Option<TestResult>> = vec![None; delays.len()];
+        for thread in threads {
+            match thread.join().unwrap() {
+                None => return Vec::new(),
+                Some(WorkerSamples::Latency(samples)) => latencies = samples,
+                Some(WorkerSamples::Bandwidth(samples)) => {
+                    for (bandwidth, sample) in bandwidths.iter_mut().zip(samples) {
+                        match bandwidth {
+                            Some(bandwidth) => bandwidth.combine_parallel(&sample),
+                            None => *bandwidth = Some(sample),
+                        }
+                    }
+                }
+            }
+        }
+        delays
+            .iter()
+            .zip(latencies)
+            .zip(bandwidths)
+            .map(
+                |((&injection_delay, latency), bandwidth)| LoadedLatencyPoint {
+                    injection_delay,
+                    latency,
+                    bandwidth: bandwidth.unwrap_or_default(),
+                },
+            )
+            .collect()
+    }
+    fn spawn_worker(&mut self, worker: usize, cpu: Option<usize>) {
+        let config = self.config.clone();
+        let delays = Arc::clone(&self.delays);
+        let finished_steps = Arc::clone(&self.finished_steps);
+        let progress = Arc::clone(&self.progress);
+        self.threads.push(std::thread::spawn(move || {
+            if worker == 0 {
+                Self::run_latency(&config, &delays, cpu, &finished_steps, &progress)
+                    .map(WorkerSamples::Latency)
+            } else {
+                Self::run_bandwidth(&config, &delays, cpu, &finished_steps, &progress)
+                    .map(WorkerSamples::Bandwidth)
+            }
+        }));
+    }
+    /// Chases pointers for the duration of every step, then tells the bandwidth threads that the
+    /// step is over
+    fn run_latency(
+        config: &Config,
+        delays: &[usize],
+        cpu: Option<usize>,
+        finished_steps: &AtomicUsize,
+        progress: &Arc<ProgressTracker<State>>,
+    ) -> Option<Vec<LatencyResult>> {
+        let cpu = cpu.filter(|&cpu| pin_current_thread(cpu));
+        let (mut memory, memory_node, backing) = allocate_thread_buffer(config, progress);
+        // The same total as the bandwidth threads initializing their buffers
+        progress.transition_state(State::Initializing, (memory.len() * config.threads) as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+        let mut current = build_pointer_chain(&mut memory, progress)?;
+        progress.add((memory.len() - memory.len() / crate::CHAIN_STRIDE) as u64);
+        let step_duration = config.run_duration.unwrap_or(DEFAULT_STEP_DURATION);
+        let mut samples = Vec::with_capacity(delays.len());
+        for step in 0..delays.len() {
+            progress.transition_state(
+                State::InjectionDelay(step + 1, delays.len()),
+                step_duration.as_millis() as u64,
+            );
+            if progress.stop_requested() {
+                finished_steps.store(step + 1, Ordering::Release);
+                return None;
+            }
+            let start = Instant::now();
+            let mut loads = 0;
+            while start.elapsed() < step_duration && !progress.stop_requested() {
+                current = unsafe { chase(current, LOADS_PER_PROGRESS_UPDATE) };
+                loads += LOADS_PER_PROGRESS_UPDATE;
+                progress.set_counter(start.elapsed().as_millis() as u64);
+            }
+            let runtime = start.elapsed();
+            finished_steps.store(step + 1, Ordering::Release);
+            samples.push(LatencyResult {
+                loads,
+                runtime,
+                pass_runtimes: Vec::new(),
+                cpu,
+                memory_node,
+                backing,
+            });
+        }
+        black_box(current);
+        progress.transition_state(State::Done, config.threads as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+        progress.add(1);
+        Some(samples)
+    }
+    /// Runs passes over the thread's buffer until the latency thread finished the step, waiting
+    /// for the step's injection delay after every cache line
+    fn run_bandwidth(
+        config: &Config,
+        delays: &[usize],
+        cpu: Option<usize>,
+        finished_steps: &AtomicUsize,
+        progress: &Arc<ProgressTracker<State>>,
+    ) -> Option<Vec<TestResult>> {
+        let cpu = cpu.filter(|&cpu| pin_current_thread(cpu));
+        let (memory, memory_node, backing) = allocate_thread_buffer(config, progress);
+        let destination = (config.operation == MemoryOperation::Copy)
+            .then(|| Destination::allocate(config, memory.len(), memory_node));
+        let mut memory = initialize_thread_buffer(config, progress, memory)?;
+        let memory = misaligned(config, &raw mut *memory);
+        let order = LineOrder::new(config, memory.len());
+        let mut samples = Vec::with_capacity(delays.len());
+        for (step, &delay) in delays.iter().enumerate() {
+            progress.transition_state(
+                State::InjectionDelay(step + 1, delays.len()),
+                config
+                    .run_duration
+                    .unwrap_or(DEFAULT_STEP_DURATION)
+                    .as_millis() as u64,
+            );
+            if progress.stop_requested() {
+                return None;
+            }
+            let chunk_size = if delay == 0 {
+                config.block_size.max(1)
+            } else {
+                CACHE_LINE
+            };
+            let mut memory_processed = 0;
+            let mut bytes_written = 0;
+            let start = Instant::now();
+            while finished_steps.load(Ordering::Acquire) <= step {
+                let destination = destination.as_ref().map(|d| misaligned(config, d.as_ptr()));
+                // SAFETY: The buffer and the destination are private to this worker
+                let finished = unsafe {
+                    execute_pass(
+                        config,
+                        memory,
+                        destination,
+                        &order,
+                        chunk_size,
+                        &mut bytes_written,
+                        |processed| {
+                            memory_processed += processed;
+                            for iteration in 0..delay {
+                                black_box(iteration);
+                            }
+                            finished_steps.load(Ordering::Relaxed) <= step
+                                && !progress.stop_requested()
+                        },
+                    )
+                };
+                if finished.is_none() {
+                    break;
+                }
+            }
+            let end = Instant::now();
+            if progress.stop_requested() {
+                return None;
+            }
+            samples.push(TestResult {
+                memory_processed,
+                memory_traffic: memory_processed * config.operation.traffic_multiplier(),
+                bytes_written,
+                runtime: end - start,
+                measure_start: Some(start),
+                measure_end: Some(end),
+                pass_runtimes: Vec::new(),
+                cpu,
+                memory_node,
+                destination_node: destination.as_ref().and_then(|d| d.node),
+                backing,
+            });
+        }
+        progress.transition_state(State::Done, config.threads as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+        progress.add(1);
+        Some(samples)
+    }
+}
@@ -0,0 +1,393 @@
+use crate::{
+    Config, Destination, LineOrder, MemoryOperation, PAGE_SIZE, State, TestResult,
+    allocate_thread_buffer, bytes_per_pass, execute_pass, initialize_thread_buffer, misaligned,
+    parse_cpu_list,
+    passes::{MeasuredPasses, PassSchedule, run_passes},
+};
+use benchmarks_core::ProgressTracker;
+use nix::{
+    sched::{CpuSet, sched_setaffinity},
+    unistd::Pid,
+};
+use std::{
+    mem::MaybeUninit,
+    path::Path,
+    sync::{Arc, LazyLock},
+};
+
+const SYS_DEVICES_SYSTEM: &str = "/sys/devices/system";
+
+/// Move pages that are already allocated on another node, see mbind(2)
+const MPOL_MF_MOVE: libc::c_uint = 1 << 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumaNode {
+    pub id: usize,
+    pub cpus: Vec<usize>,
+}
+
+/// The NUMA nodes of this machine that have CPUs or memory attached.
+///
+/// Machines without NUMA support are reported as a single node 0 containing every CPU.
+pub static NUMA_NODES: LazyLock<Vec<NumaNode>> =
+    LazyLock::new(|| read_nodes(Path::new(SYS_DEVICES_SYSTEM)));
+
+/// Reads the NUMA nodes from `system`, the sysfs `devices/system` directory, falling back to a
+/// single node 0 with every online CPU if it has no NUMA nodes
+fn read_nodes(system: &Path) -> Vec<NumaNode> {
+    let nodes = discover_nodes(&system.join("node"));
+    if !nodes.is_empty() {
+        return nodes;
+    }
+    let cpus = std::fs::read_to_string(system.join("cpu/online"))
+        .ok()
+        .and_then(|list| parse_cpu_list(&list))
+        .unwrap_or_default();
+    vec![NumaNode { id: 0, cpus }]
+}
+
+fn discover_nodes(node_dir: &Path) -> Vec<NumaNode> {
+    let Ok(online) = std::fs::read_to_string(node_dir.join("online")) else {
+        return Vec::new();
+    };
+    let Some(ids) = parse_cpu_list(&online) else {
+        return Vec::new();
+    };
+    ids.into_iter()
+        .map(|id| NumaNode {
+            id,
+            cpus: std::fs::read_to_string(node_dir.join(format!("node{id}/cpulist")))
+                .ok()
+                .and_then(|list| parse_cpu_list(&list))
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Binds the pages backing `memory` to `node`, moving any that were already faulted in elsewhere.
+/// Returns whether that succeeded, which it won't on kernels without NUMA support.
+pub(crate) fn bind_to_node(memory: &mut [MaybeUninit<u8>], node: usize) -> bool {
+    const MASK_BITS: usize = libc::c_ulong::BITS as usize;
+    let mut nodemask = [0 as libc::c_ulong; 1024 / MASK_BITS];
+    let Some(word) = nodemask.get_mut(node / MASK_BITS) else {
+        return false;
+    };
+    *word |= 1 << (node % MASK_BITS);
+    // mbind requires a page-aligned start address
+    let offset = memory.as_ptr().align_offset(*PAGE_SIZE);
+    let Some(memory) = memory.get_mut(offset..) else {
+        return false;
+    };
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_mbind,
+            memory.as_mut_ptr(),
+            memory.len(),
+            libc::MPOL_BIND,
+            nodemask.as_ptr(),
+            nodemask.len() * MASK_BITS,
+            MPOL_MF_MOVE,
+        )
+    };
+    result == 0
+}
+
+/// Allows the calling thread to run on any CPU of `node`, returning whether that succeeded
+fn pin_current_thread_to_node(node: &NumaNode) -> bool {
+    let mut set = CpuSet::new();
+    for &cpu in &node.cpus {
+        if set.set(cpu).is_err() {
+            return false;
+        }
+    }
+    !node.cpus.is_empty() && sched_setaffinity(Pid::from_raw(0), &set).is_ok()
+}
+
+/// Throughput measured with the workers running on one node and their memory on another
+#[derive(Debug, Clone)]
+pub struct NumaMatrixEntry {
+    pub cpu_node: usize,
+    pub memory_node: usize,
+    pub result: TestResult,
+}
+
+#[derive(Debug)]
+pub struct NumaMatrixBench {
+    config: Config,
+    schedule: PassSchedule,
+    threads: Vec<std::thread::JoinHandle<Option<Vec<NumaMatrixEntry>>>>,
+    progress: Arc<ProgressTracker<State>>,
+}
+
+impl Config {
+    /// Measures `operation` for every pair of a CPU node and a memory node, with all workers
+    /// running on the CPU node and every buffer bound to the memory node.
+    /// `pinning` and `memory_node` are ignored, and copies always go to a buffer on the same
+    /// memory node regardless of `copy_placement`.
+    #[must_use]
+    pub fn start_numa_matrix(self) -> NumaMatrixBench {
+        let progress = Arc::new(ProgressTracker::new(
+            self.threads as u64,
+            self.threads,
+            State::Allocating,
+        ));
+        let mut bench = NumaMatrixBench {
+            config: self,
+            schedule: PassSchedule::default(),
+            threads: Vec::new(),
+            progress,
+        };
+        for _ in 0..bench.config.threads {
+            bench.spawn_worker();
+        }
+        bench
+    }
+}
+
+impl NumaMatrixBench {
+    #[must_use]
+    pub fn progress(&self) -> Arc<ProgressTracker<State>> {
+        Arc::clone(&self.progress)
+    }
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        (self.progress.stop_requested() || self.progress.load_state() == State::Done) && {
+            self.threads.iter().all(|t| t.is_finished())
+        }
+    }
+    /// Combines the results of all threads, in CPU node major order.
+    /// The runtime of every entry is that of the slowest thread.
+    #[must_use]
+    pub fn wait_for_results(self) -> Vec<NumaMatrixEntry> {
+        let Self { threads, .. } = self;
+        let mut matrix: Vec<NumaMatrixEntry> = Vec::new();
+        for thread in threads {
+            let Some(entries) = thread.join().unwrap() else {
+                continue;
+            };
+            if matrix.is_empty() {
+                matrix = entries;
+                continue;
+            }
+            for (total, entry) in matrix.iter_mut().zip(entries) {
+                total.result.combine_parallel(&entry.result);
+            }
+        }
+        matrix
+    }
+    fn spawn_worker(&mut self) {
+        let config = self.config.clone();
+        let mut schedule = self.schedule.clone();
+        let progress = Arc::clone(&self.progress);
+        self.threads.push(std::thread::spawn(move || {
+            Self::run(config, &mut schedule, &progress)
+        }));
+    }
+    fn run(
+        mut config: Config,
+        schedule: &mut PassSchedule,
+        progress: &Arc<ProgressTracker<State>>,
+    ) -> Option<Vec<NumaMatrixEntry>> {
+        let chunk_size = config.block_size.max(1);
+        let nodes = &*NUMA_NODES;
+        let mut matrix = Vec::with_capacity(nodes.len() * nodes.len());
+        for (cpu_idx, cpu_node) in nodes.iter().enumerate() {
+            for (memory_idx, memory_node) in nodes.iter().enumerate() {
+                progress.transition_state(
+                    State::NodePair(cpu_idx * nodes.len() + memory_idx + 1, nodes.len().pow(2)),
+                    config.threads as u64,
+                );
+                if progress.stop_requested() {
+                    return None;
+                }
+                // Nodes without CPUs can still be measured as memory nodes
+                if !pin_current_thread_to_node(cpu_node) {
+                    progress.add(1);
+                    continue;
+                }
+                config.memory_node = Some(memory_node.id);
+                let (memory, bound_node, backing) = allocate_thread_buffer(&config, progress);
+                let destination = (config.operation == MemoryOperation::Copy)
+                    .then(|| Destination::allocate(&config, memory.len(), bound_node));
+                let mut memory = initialize_thread_buffer(&config, progress, memory)?;
+                let memory = misaligned(&config, &raw mut *memory);
+
+                let order = LineOrder::new(&config, memory.len());
+                let pass_bytes = bytes_per_pass(&config, memory.len());
+                // Every pass writes the same bytes
+                let mut pass_bytes_written = 0;
+                let MeasuredPasses {
+                    runtimes: pass_runtimes,
+                    work: memory_processed,
+                    start: measure_start,
+                    end: measure_end,
+                } = run_passes(&config, progress, schedule, pass_bytes, |on_chunk| {
+                    let destination = destination
+                        .as_ref()
+                        .map(|d| misaligned(&config, d.as_ptr()));
+                    pass_bytes_written = 0;
+                    // SAFETY: The buffer and the destination are private to this worker
+                    unsafe {
+                        execute_pass(
+                            &config,
+                            memory,
+                            destination,
+                            &order,
+                            chunk_size,
+                            &mut pass_bytes_written,
+                            on_chunk,
+                        )
+                    }
+                })?;
+                matrix.push(NumaMatrixEntry {
+                    cpu_node: cpu_node.id,
+                    memory_node: memory_node.id,
+                    result: TestResult {
+                        memory_processed,
+                        memory_traffic: memory_processed * config.operation.traffic_multiplier(),
+                        bytes_written: pass_bytes_written * pass_runtimes.len(),
+                        runtime: pass_runtimes.iter().sum(),
+                        pass_runtimes,
+                        measure_start,
+                        measure_end,
+                        cpu: None,
+                        memory_node: bound_node,
+                        destination_node: destination.and_then(|d| d.node),
+                        backing,
+                    },
+                });
+            }
+        }
+        progress.transition_state(State::Done, config.threads as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+        progress.add(1);
+        Some(matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A fake sysfs `devices/system` directory, removed again on drop
+    struct FakeSystem(PathBuf);
+
+    impl FakeSystem {
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let root = std::env::temp_dir().join(format!(
+                "benchmarks-memory-numa-{name}-{}",
+                std::process::id()
+            ));
+            _ = std::fs::remove_dir_all(&root);
+            for (path, contents) in files {
+                let path = root.join(path);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, contents).unwrap();
+            }
+            std::fs::create_dir_all(&root).unwrap();
+            Self(root)
+        }
+    }
+
+    impl Drop for FakeSystem {
+        fn drop(&mut self) {
+            _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn reads_every_online_node() {
+        let system = FakeSystem::new(
+            "multi-node",
+            &[
+                ("cpu/online", "0-7\n"),
+                ("node/online", "0-1,3\n"),
+                ("node/node0/cpulist", "0-3\n"),
+                ("node/node1/cpulist", "4-7\n"),
+                // A memory-only node
+                ("node/node3/cpulist", "\n"),
+            ],
+        );
+        assert_eq!(
+            read_nodes(&system.0),
+            [
+                NumaNode {
+                    id: 0,
+                    cpus: vec![0, 1, 2, 3]
+                },
+                NumaNode {
+                    id: 1,
+                    cpus: vec![4, 5, 6, 7]
+                },
+                NumaNode {
+                    id: 3,
+                    cpus: vec![]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_a_single_node_without_numa() {
+        let system = FakeSystem::new("no-numa", &[("cpu/online", "0-3\n")]);
+        assert_eq!(
+            read_nodes(&system.0),
+            [NumaNode {
+                id: 0,
+                cpus: vec![0, 1, 2, 3]
+            }]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_an_empty_node_without_sysfs() {
+        let system = FakeSystem::new("no-sysfs", &[]);
+        assert_eq!(
+            read_nodes(&system.0),
+            [NumaNode {
+                id: 0,
+                cpus: vec![]
+            }]
+        );
+    }
+
+    #[test]
+    fn malformed_lists_are_ignored() {
+        let system = FakeSystem::new(
+            "malformed-cpulist",
+            &[
+                ("cpu/online", "0-3\n"),
+                ("node/online", "0-1\n"),
+                ("node/node0/cpulist", "0-1\n"),
+                ("node/node1/cpulist", "3-2\n"),
+            ],
+        );
+        assert_eq!(
+            read_nodes(&system.0),
+            [
+                NumaNode {
+                    id: 0,
+                    cpus: vec![0, 1]
+                },
+                NumaNode {
+                    id: 1,
+                    cpus: vec![]
+                },
+            ]
+        );
+        let system = FakeSystem::new(
+            "malformed-online",
+            &[("cpu/online", "0-3\n"), ("node/online", "zero\n")],
+        );
+        assert_eq!(
+            read_nodes(&system.0),
+            [NumaNode {
+                id: 0,
+                cpus: vec![0, 1, 2, 3]
+            }]
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{Config, State};
+use benchmarks_core::ProgressTracker;
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// Keeps the workers of a benchmark agreeing on when a time-bounded run ends.
+///
+/// Every worker owns a clone, they share the index of the pass that finished past the deadline.
+/// Since all workers wait for each other before every pass, a pass only starts once every worker
+/// has reported whether the previous one hit the deadline.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PassSchedule {
+    /// One past the index of the latest pass that finished past the deadline, 0 if none did
+    deadline_pass: Arc<AtomicUsize>,
+    /// The index of the next pass of this worker, counted across all runs
+    next_pass: usize,
+}
+
+/// The timing of the measured passes of a single worker
+#[derive(Debug, Default)]
+pub(crate) struct MeasuredPasses {
+    pub runtimes: Vec<Duration>,
+    /// The units of work reported by all measured passes together
+    pub work: usize,
+    /// When the first measured pass started, `None` if there were no measured passes
+    pub start: Option<Instant>,
+    /// When the last measured pass finished
+    pub end: Option<Instant>,
+}
+
+/// Runs `config.warmup_passes` unmeasured passes followed by either `config.passes` measured
+/// passes or as many as fit into `config.run_duration`, keeping all workers in lockstep.
+///
+/// `pass` runs a single pass over `work_per_pass` units of work, reporting its progress to the
+/// callback it gets, which returns `false` once the benchmark should stop.
+/// While running for a fixed duration the progress bar follows the time instead.
+///
+/// The state transition before the first measured pass doubles as the common start barrier, every
+/// worker starts measuring as soon as it is released from it.
+///
+/// Returns the timing of the measured passes, or `None` if a stop was requested.
+pub(crate) fn run_passes(
+    config: &Config,
+    progress: &ProgressTracker<State>,
+    schedule: &mut PassSchedule,
+    work_per_pass: usize,
+    mut pass: impl FnMut(&mut dyn FnMut(usize) -> bool) -> Option<()>,
+) -> Option<MeasuredPasses> {
+    let total_work = (work_per_pass * config.threads) as u64;
+    let mut report_work = |work: usize| {
+        progress.add(work as u64);
+        !progress.stop_requested()
+    };
+    for warmup in 0..config.warmup_passes {
+        progress.transition_state(
+            State::WarmingUp(warmup + 1, config.warmup_passes),
+            total_work,
+        );
+        if progress.stop_requested() {
+            return None;
+        }
+        pass(&mut report_work)?;
+    }
+    let mut measured_passes = MeasuredPasses::default();
+    let Some(duration) = config.run_duration else {
+        for measured in 0..config.passes {
+            progress.transition_state(State::Executing(measured + 1, config.passes), total_work);
+            if progress.stop_requested() {
+                return None;
+            }
+            let start = Instant::now();
+            measured_passes.start.get_or_insert(start);
+            pass(&mut |work| {
+                measured_passes.work += work;
+                report_work(work)
+            })?;
+            let end = Instant::now();
+            measured_passes.runtimes.push(end - start);
+            measured_passes.end = Some(end);
+        }
+        return Some(measured_passes);
+    };
+    let first_pass = schedule.next_pass;
+    for measured in 0.. {
+        let index = schedule.next_pass;
+        schedule.next_pass += 1;
+        progress.transition_state(
+            State::Executing(measured + 1, 0),
+            duration.as_millis() as u64,
+        );
+        if progress.stop_requested() {
+            return None;
+        }
+        let deadline_pass = schedule.deadline_pass.load(Ordering::Acquire);
+        if deadline_pass > first_pass && deadline_pass <= index {
+            break;
+        }
+        let start = Instant::now();
+        let run_start = *measured_passes.start.get_or_insert(start);
+        pass(&mut |work| {
+            measured_passes.work += work;
+            progress.set_counter(run_start.elapsed().as_millis() as u64);
+            !progress.stop_requested()
+        })?;
+        let end = Instant::now();
+        measured_passes.runtimes.push(end - start);
+        measured_passes.end = Some(end);
+        if end - run_start >= duration {
+            schedule.deadline_pass.store(index + 1, Ordering::Release);
+        }
+    }
+    Some(measured_passes)
+}
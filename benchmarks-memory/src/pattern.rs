@@ -0,0 +1,196 @@
+use crate::Config;
+use benchmarks_core::SelectableEnum;
+use rand::{SeedableRng, seq::SliceRandom};
+
+/// The granularity at which non-sequential access patterns visit memory
+pub const CACHE_LINE: usize = 64;
+
+/// The order in which a pass visits the memory of a buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccessPattern {
+    /// Front to back, a chunk at a time
+    #[default]
+    Sequential,
+    /// Every `Config::stride` bytes, wrapping around until every cache line was visited
+    Strided,
+    /// Back to front, a cache line at a time
+    Reverse,
+    /// Every cache line once, in a random order seeded by `Config::pattern_seed`
+    Random,
+}
+
+impl SelectableEnum for AccessPattern {
+    fn all_values() -> &'static [Self] {
+        use AccessPattern::*;
+        &[Sequential, Strided, Reverse, Random]
+    }
+    fn as_str(&self) -> &'static str {
+        use AccessPattern::*;
+        match self {
+            Sequential => "Sequential",
+            Strided => "Strided",
+            Reverse => "Reverse",
+            Random => "Random",
+        }
+    }
+}
+
+/// How many cache lines [`crate::MemoryOperation::Mixed`] reads for every `writes` cache lines it
+/// writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadWriteRatio {
+    pub reads: usize,
+    pub writes: usize,
+}
+
+impl Default for ReadWriteRatio {
+    fn default() -> Self {
+        Self {
+            reads: 3,
+            writes: 1,
+        }
+    }
+}
+
+impl std::fmt::Display for ReadWriteRatio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.reads, self.writes)
+    }
+}
+
+impl ReadWriteRatio {
+    /// Whether the `idx`th cache line a pass visits gets written rather than read.
+    /// The writes of every group of `reads + writes` lines are spread evenly among its reads.
+    pub(crate) fn is_write(&self, idx: usize) -> bool {
+        let group = self.reads + self.writes;
+        if group == 0 {
+            return false;
+        }
+        let idx = idx % group;
+        (idx + 1) * self.writes / group > idx * self.writes / group
+    }
+}
+
+/// The order a pass visits the cache lines of a buffer in, prepared once per buffer
+#[derive(Debug)]
+pub(crate) enum LineOrder {
+    Sequential,
+    Strided {
+        lines: usize,
+        stride_lines: usize,
+    },
+    Reverse {
+        lines: usize,
+    },
+    Permuted(Vec<u32>),
+    /// Every `step`th cache line starting at `first`, the `lines` of them visited in `owned`
+    Interleaved {
+        first: usize,
+        step: usize,
+        lines: usize,
+        /// Whether the bytes after the last whole cache line belong to these lines
+        visits_tail: bool,
+        owned: Box<LineOrder>,
+    },
+}
+
+impl LineOrder {
+    /// Prepares the order of `config.access_pattern` for a buffer of `len` bytes
+    pub(crate) fn new(config: &Config, len: usize) -> Self {
+        let lines = len / CACHE_LINE;
+        match config.access_pattern {
+            AccessPattern::Sequential => LineOrder::Sequential,
+            AccessPattern::Strided => LineOrder::Strided {
+                lines,
+                stride_lines: (config.stride / CACHE_LINE).max(1),
+            },
+            AccessPattern::Reverse => LineOrder::Reverse { lines },
+            AccessPattern::Random => {
+                let mut rng = rand::rngs::SmallRng::seed_from_u64(config.pattern_seed);
+                let mut order: Vec<u32> = (0..lines as u32).collect();
+                order.shuffle(&mut rng);
+                LineOrder::Permuted(order)
+            }
+        }
+    }
+    /// Prepares the order of `config.access_pattern` over the cache lines of a buffer of `len`
+    /// bytes that `worker` owns, see [`crate::BufferSharing::Interleaved`]
+    pub(crate) fn interleaved(config: &Config, len: usize, worker: usize) -> Self {
+        let total_lines = len / CACHE_LINE;
+        let step = config.threads.max(1);
+        let lines = total_lines.saturating_sub(worker).div_ceil(step);
+        LineOrder::Interleaved {
+            first: worker,
+            step,
+            lines,
+            visits_tail: !len.is_multiple_of(CACHE_LINE) && total_lines % step == worker,
+            owned: Box::new(LineOrder::new(config, lines * CACHE_LINE)),
+        }
+    }
+    /// Whether a pass in this order covers the bytes after the last whole cache line
+    pub(crate) fn visits_tail(&self) -> bool {
+        match *self {
+            LineOrder::Interleaved { visits_tail, .. } => visits_tail,
+            _ => true,
+        }
+    }
+    /// The bytes of a buffer of `len` bytes a pass in this order covers
+    pub(crate) fn pass_len(&self, len: usize) -> usize {
+        match *self {
+            LineOrder::Interleaved { lines, .. } if self.visits_tail() => {
+                lines * CACHE_LINE + len % CACHE_LINE
+            }
+            LineOrder::Interleaved { lines, .. } => lines * CACHE_LINE,
+            _ => len,
+        }
+    }
+    /// Calls `visit` with the index of every cache line of the buffer, in order.
+    /// Must not be called on [`LineOrder::Sequential`].
+    ///
+    /// Returns `None` as soon as `visit` returns `false`.
+    pub(crate) fn for_each_line(&self, mut visit: impl FnMut(usize) -> bool) -> Option<()> {
+        match *self {
+            LineOrder::Sequential => unreachable!("sequential passes work on whole chunks"),
+            LineOrder::Strided {
+                lines,
+                stride_lines,
+            } => {
+                for start in 0..stride_lines.min(lines) {
+                    for line in (start..lines).step_by(stride_lines) {
+                        visit(line).then_some(())?;
+                    }
+                }
+            }
+            LineOrder::Reverse { lines } => {
+                for line in (0..lines).rev() {
+                    visit(line).then_some(())?;
+                }
+            }
+            LineOrder::Permuted(ref order) => {
+                for &line in order {
+                    visit(line as usize).then_some(())?;
+                }
+            }
+            LineOrder::Interleaved {
+                first,
+                step,
+                lines,
+                ref owned,
+                ..
+            } => match **owned {
+                LineOrder::Sequential => {
+                    for line in 0..lines {
+                        visit(first + line * step).then_some(())?;
+                    }
+                }
+                ref owned => {
+                    // Dynamic dispatch ends the recursion through the nested orders
+                    let visit: &mut dyn FnMut(usize) -> bool =
+                        &mut |line| visit(first + line * step);
+                    owned.for_each_line(visit)?;
+                }
+            },
+        }
+        Some(())
+    }
+}
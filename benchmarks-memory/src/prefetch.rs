@@ -0,0 +1,218 @@
+use crate::{
+    Config, Destination, LineOrder, MemoryOperation, State, TestResult, allocate_thread_buffer,
+    execute_pass, initialize_thread_buffer, misaligned, pin_current_thread,
+};
+use benchmarks_core::{ProgressTracker, SelectableEnum};
+use std::{sync::Arc, time::Instant};
+
+/// How far ahead the prefetching strategies prefetch unless `Config::prefetch_distance` says
+/// otherwise, in bytes
+pub const DEFAULT_PREFETCH_DISTANCE: usize = 512;
+
+/// The prefetch distances of a prefetch sweep, from a single cache line up to four pages
+pub const DEFAULT_PREFETCH_DISTANCES: &[usize] =
+    &[64, 128, 256, 512, 1024, 2048, 4096, 8192, 16384];
+
+/// The cache level a prefetch pulls a cache line into, mirroring `_mm_prefetch`'s hints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrefetchHint {
+    /// Into every cache level
+    #[default]
+    T0,
+    /// Into L2 and beyond
+    T1,
+    /// Into L3 and beyond
+    T2,
+    /// Close to the core while bypassing as much of the cache hierarchy as possible, for data
+    /// that is only used once
+    Nta,
+}
+
+impl SelectableEnum for PrefetchHint {
+    fn all_values() -> &'static [Self] {
+        use PrefetchHint::*;
+        &[T0, T1, T2, Nta]
+    }
+    fn as_str(&self) -> &'static str {
+        use PrefetchHint::*;
+        match self {
+            T0 => "T0 (all levels)",
+            T1 => "T1 (L2 and up)",
+            T2 => "T2 (L3 and up)",
+            Nta => "NTA (non-temporal)",
+        }
+    }
+}
+
+/// The throughput of all threads at a single prefetch distance
+#[derive(Debug, Clone)]
+pub struct PrefetchSweepPoint {
+    /// How far ahead every cache line got prefetched, in bytes
+    pub distance: usize,
+    pub result: TestResult,
+}
+
+#[derive(Debug)]
+pub struct PrefetchSweepBench {
+    config: Config,
+    distances: Arc<[usize]>,
+    threads: Vec<std::thread::JoinHandle<Option<Vec<TestResult>>>>,
+    progress: Arc<ProgressTracker<State>>,
+}
+
+impl Config {
+    /// Starts a sweep running `passes` passes of `operation` with `strategy` at every prefetch
+    /// distance of `distances` in turn, prefetching with `prefetch_hint`. Only meaningful for
+    /// [prefetching](crate::OperationStrategy::is_prefetching) strategies, the others ignore the
+    /// distance.
+    ///
+    /// `prefetch_distance`, `warmup_passes` and `run_duration` are ignored, and copies always go
+    /// to a buffer of the same thread regardless of `copy_placement`.
+    #[must_use]
+    pub fn start_prefetch_sweep(self, distances: &[usize]) -> PrefetchSweepBench {
+        let progress = Arc::new(ProgressTracker::new(
+            self.threads as u64,
+            self.threads,
+            State::Allocating,
+        ));
+        let cpus = self.pinning_order();
+        let mut bench = PrefetchSweepBench {
+            config: self,
+            distances: distances.into(),
+            threads: Vec::new(),
+            progress,
+        };
+        for worker in 0..bench.config.threads {
+            bench.spawn_worker(cpus.get(worker % cpus.len().max(1)).copied());
+        }
+        bench
+    }
+}
+
+impl PrefetchSweepBench {
+    #[must_use]
+    pub fn progress(&self) -> Arc<ProgressTracker<State>> {
+        Arc::clone(&self.progress)
+    }
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        (self.progress.stop_requested() || self.progress.load_state() == State::Done) && {
+            self.threads.iter().all(|t| t.is_finished())
+        }
+    }
+    /// Combines the results of all threads into a throughput-vs-distance series, in the order
+    /// of the distances
+    #[must_use]
+    pub fn wait_for_results(self) -> Vec<PrefetchSweepPoint> {
+        let Self {
+            threads, distances, ..
+        } = self;
+        let mut points: Vec<PrefetchSweepPoint> = distances
+            .iter()
+            .map(|&distance| PrefetchSweepPoint {
+                distance,
+                result: TestResult::default(),
+            })
+            .collect();
+        let mut any_finished = false;
+        for thread in threads {
+            let Some(samples) = thread.join().unwrap() else {
+                continue;
+            };
+            for (point, sample) in points.iter_mut().zip(samples) {
+                if any_finished {
+                    point.result.combine_parallel(&sample);
+                } else {
+                    point.result = sample;
+                }
+            }
+            any_finished = true;
+        }
+        if !any_finished {
+            points.clear();
+        }
+        points
+    }
+    fn spawn_worker(&mut self, cpu: Option<usize>) {
+        let config = self.config.clone();
+        let distances = Arc::clone(&self.distances);
+        let progress = Arc::clone(&self.progress);
+        self.threads.push(std::thread::spawn(move || {
+            Self::run(config, &distances, cpu, &progress)
+        }));
+    }
+    fn run(
+        mut config: Config,
+        distances: &[usize],
+        cpu: Option<usize>,
+        progress: &Arc<ProgressTracker<State>>,
+    ) -> Option<Vec<TestResult>> {
+        let cpu = cpu.filter(|&cpu| pin_current_thread(cpu));
+        let (memory, memory_node, backing) = allocate_thread_buffer(&config, progress);
+        let destination = (config.operation == MemoryOperation::Copy)
+            .then(|| Destination::allocate(&config, memory.len(), memory_node));
+        let mut memory = initialize_thread_buffer(&config, progress, memory)?;
+        let memory = misaligned(&config, &raw mut *memory);
+        let order = LineOrder::new(&config, memory.len());
+        let chunk_size = config.block_size.max(1);
+        let passes = config.passes.max(1);
+        let mut results = Vec::with_capacity(distances.len());
+        for (step, &distance) in distances.iter().enumerate() {
+            config.prefetch_distance = distance;
+            progress.transition_state(
+                State::PrefetchDistance(step + 1, distances.len()),
+                (passes * config.threads) as u64,
+            );
+            if progress.stop_requested() {
+                return None;
+            }
+            let mut memory_processed = 0;
+            let mut bytes_written = 0;
+            let mut pass_runtimes = Vec::with_capacity(passes);
+            let start = Instant::now();
+            for _ in 0..passes {
+                let destination = destination
+                    .as_ref()
+                    .map(|d| misaligned(&config, d.as_ptr()));
+                let pass_start = Instant::now();
+                // SAFETY: The buffer and the destination are private to this worker
+                unsafe {
+                    execute_pass(
+                        &config,
+                        memory,
+                        destination,
+                        &order,
+                        chunk_size,
+                        &mut bytes_written,
+                        |processed| {
+                            memory_processed += processed;
+                            !progress.stop_requested()
+                        },
+                    )
+                }?;
+                pass_runtimes.push(pass_start.elapsed());
+                progress.add(1);
+            }
+            let end = Instant::now();
+            results.push(TestResult {
+                memory_processed,
+                memory_traffic: memory_processed * config.operation.traffic_multiplier(),
+                bytes_written,
+                runtime: end - start,
+                measure_start: Some(start),
+                measure_end: Some(end),
+                pass_runtimes,
+                cpu,
+                memory_node,
+                destination_node: destination.as_ref().and_then(|d| d.node),
+                backing,
+            });
+        }
+        progress.transition_state(State::Done, config.threads as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+        progress.add(1);
+        Some(results)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{
+    AllocatedBacking, Config, MemoryOperation, OperationStrategy, OwnedPtr, PAGE_SIZE,
+    allocate_bound_buffer,
+};
+use benchmarks_core::SelectableEnum;
+use std::{
+    mem::MaybeUninit,
+    sync::{Arc, Mutex},
+};
+
+/// Whether the workers of a throughput benchmark each work on their own buffer or all on one.
+///
+/// Workers sharing a buffer race on it by design: the values they read are never relied upon,
+/// only the traffic they cause is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BufferSharing {
+    /// Every worker allocates and works on its own `memory_size / threads` bytes
+    #[default]
+    Private,
+    /// All workers work on a single buffer of `memory_size` bytes, every worker visiting every
+    /// `threads`th cache line starting at its own index
+    Interleaved,
+    /// All workers work on every byte of a single buffer of `memory_size` bytes
+    Whole,
+}
+
+impl SelectableEnum for BufferSharing {
+    fn all_values() -> &'static [Self] {
+        use BufferSharing::*;
+        &[Private, Interleaved, Whole]
+    }
+    fn as_str(&self) -> &'static str {
+        use BufferSharing::*;
+        match self {
+            Private => "Private",
+            Interleaved => "Interleaved lines",
+            Whole => "Whole buffer",
+        }
+    }
+}
+
+impl Config {
+    /// Whether the workers of [`Config::start`] share a single buffer. Only reads, writes and
+    /// mixed passes can, copies and the STREAM kernels always work on private buffers. So does
+    /// [`OperationStrategy::RustStd`], which writes through a `&mut [u8]`.
+    #[must_use]
+    pub fn shares_buffer(&self) -> bool {
+        self.buffer_sharing != BufferSharing::Private
+            && self.strategy != OperationStrategy::RustStd
+            && matches!(
+                self.operation,
+                MemoryOperation::Read | MemoryOperation::Write | MemoryOperation::Mixed
+            )
+    }
+}
+
+/// The buffer all workers share unless `Config::buffer_sharing` is [`BufferSharing::Private`]
+pub(crate) struct SharedBuffer {
+    memory: OwnedPtr<[MaybeUninit<u8>]>,
+    /// The NUMA node the buffer was bound to, if any
+    pub node: Option<usize>,
+    pub backing: AllocatedBacking,
+}
+
+// SAFETY: The buffer is initialized by a single worker before any other one touches it, after
+// that the workers race on it by design, see `BufferSharing`
+unsafe impl Send for SharedBuffer {}
+unsafe impl Sync for SharedBuffer {}
+
+impl std::fmt::Debug for SharedBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedBuffer")
+            .field("len", &self.memory.len())
+            .field("node", &self.node)
+            .field("backing", &self.backing)
+            .finish()
+    }
+}
+
+impl SharedBuffer {
+    /// The length of the shared buffer of `config`, `memory_size` rounded up to whole pages
+    pub(crate) fn len(config: &Config) -> usize {
+        config.memory_size.max(1).next_multiple_of(*PAGE_SIZE)
+    }
+    /// Allocates the shared buffer with `config.backing`, binding it to `config.memory_node` if
+    /// possible. The buffer is left uninitialized.
+    pub(crate) fn allocate(config: &Config) -> Self {
+        let (memory, node, backing) = allocate_bound_buffer(config, Self::len(config));
+        Self {
+            memory,
+            node,
+            backing,
+        }
+    }
+    /// The buffer the workers race on, uninitialized until the first worker fills it. Other
+    /// workers may access it concurrently, so it must only be accessed through the pointer and
+    /// its contents may change at any time.
+    pub(crate) fn as_ptr(&self) -> *mut [u8] {
+        self.memory.ptr as *mut [u8]
+    }
+}
+
+/// Hands the shared buffer from the worker allocating it to all others.
+///
+/// The first worker publishes the buffer before the state transition that ends allocation, every
+/// worker takes it after it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedBufferExchange(Arc<Mutex<Option<Arc<SharedBuffer>>>>);
+
+impl SharedBufferExchange {
+    pub(crate) fn publish(&self, buffer: Arc<SharedBuffer>) {
+        *self.0.lock().unwrap() = Some(buffer);
+    }
+    pub(crate) fn get(&self) -> Arc<SharedBuffer> {
+        Arc::clone(
+            self.0
+                .lock()
+                .unwrap()
+                .as_ref()
+                .expect("the first worker publishes the shared buffer before taking it"),
+        )
+    }
+}
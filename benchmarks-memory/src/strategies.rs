@@ -1,7 +1,49 @@
 use benchmarks_core::SelectableEnum;
 
 use super::strategy_internals::*;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::DEFAULT_PREFETCH_DISTANCE;
+use crate::{
+    Config, MemoryOperation, PrefetchHint,
+    stream::{self, StreamKernelFn},
+};
 use std::hint::black_box;
+
+/// Copies `len` bytes from `from` to `to`, prefetching the source `distance` bytes ahead of every
+/// cache line, returning how many bytes were copied
+///
+/// # Safety
+/// `from` must be valid for reads and `to` for writes of `len` bytes, and the two must not overlap
+pub type PrefetchingCopyFn =
+    unsafe fn(from: *const u8, to: *mut u8, len: usize, distance: usize) -> usize;
+
+/// Reads every one of the `len` bytes at `data`, returning how many bytes were read.
+///
+/// The buffer is only ever accessed through the pointer, so other threads may access it at the
+/// same time.
+///
+/// # Safety
+/// `data` must be valid for reads of `len` bytes
+pub type ReadFn = unsafe fn(data: *const u8, len: usize) -> usize;
+
+/// Reads every one of the `len` bytes at `data` like a [`ReadFn`], prefetching `distance` bytes
+/// ahead of every cache line
+///
+/// # Safety
+/// `data` must be valid for reads of `len` bytes
+pub type PrefetchingReadFn = unsafe fn(data: *const u8, len: usize, distance: usize) -> usize;
+
+/// Fills the `len` bytes at `data` with [`WRITE_PATTERN`], returning how many bytes were
+/// written.
+///
+/// Except for [`OperationStrategy::RustStd`], which fills a `&mut [u8]`, the buffer is only ever
+/// accessed through the pointer, so other threads may access it at the same time.
+///
+/// # Safety
+/// `data` must be valid for writes of `len` bytes, and no other thread may access them while
+/// [`OperationStrategy::RustStd`] writes
+pub type WriteFn = unsafe fn(data: *mut u8, len: usize) -> usize;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum OperationStrategy {
     #[default]
@@ -15,8 +57,32 @@ pub enum OperationStrategy {
     AVX2,
     #[cfg(target_arch = "x86_64")]
     AVX512,
+    #[cfg(target_arch = "aarch64")]
+    Neon,
+    /// `ldp`/`stp` of two NEON registers, `ldnp`/`stnp` when non-temporal
+    #[cfg(target_arch = "aarch64")]
+    NeonPair,
+    /// `rep movsb` and `rep stosb`, available on CPUs with enhanced or fast short string
+    /// operations (ERMS/FSRM)
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    RepMovsb,
+    /// 128-bit SSE loads and stores, reads and copies prefetching `Config::prefetch_distance`
+    /// bytes ahead of every cache line with `Config::prefetch_hint`
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    Prefetch,
+    /// The baseline of what most programs use: libc's `memcpy` and `memset`
+    Libc,
+    /// The standard library's `<[u8]>::copy_from_slice` and `<[u8]>::fill`, which the compiler may
+    /// inline or turn into calls to libc
+    RustStd,
 }
 
+/// The 32 bytes a single load or store pair of [`OperationStrategy::NeonPair`] moves
+#[cfg(target_arch = "aarch64")]
+#[derive(Clone, Copy)]
+#[repr(C, align(32))]
+struct RegisterPair([u8; 32]);
+
 impl SelectableEnum for OperationStrategy {
     fn all_values() -> &'static [Self] {
         use OperationStrategy::*;
@@ -31,6 +97,16 @@ impl SelectableEnum for OperationStrategy {
             AVX2,
             #[cfg(target_arch = "x86_64")]
             AVX512,
+            #[cfg(target_arch = "aarch64")]
+            Neon,
+            #[cfg(target_arch = "aarch64")]
+            NeonPair,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            RepMovsb,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Prefetch,
+            Libc,
+            RustStd,
         ]
     }
     fn as_str(&self) -> &'static str {
@@ -46,180 +122,988 @@ impl SelectableEnum for OperationStrategy {
             AVX2 => "256-bit AVX",
             #[cfg(target_arch = "x86_64")]
             AVX512 => "512-bit AVX",
+            #[cfg(target_arch = "aarch64")]
+            Neon => "128-bit NEON",
+            #[cfg(target_arch = "aarch64")]
+            NeonPair => "NEON ldp/stp",
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            RepMovsb => "rep movsb/stosb",
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Prefetch => "128-bit SSE + prefetch",
+            Libc => "libc memcpy/memset",
+            RustStd => "Rust copy_from_slice/fill",
         }
     }
     fn is_enabled(&self) -> bool {
         use OperationStrategy::*;
+        #[cfg(target_arch = "aarch64")]
+        use std::arch::is_aarch64_feature_detected;
         #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
         use std::arch::is_x86_feature_detected;
         match self {
-            Bytewise | Int32 | Int64 | Int128 => true,
+            Bytewise | Int32 | Int64 | Int128 | Libc | RustStd => true,
             #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-            SSE => is_x86_feature_detected!("sse"),
+            SSE | Prefetch => is_x86_feature_detected!("sse2"),
             #[cfg(target_arch = "x86_64")]
             AVX2 => is_x86_feature_detected!("avx2"),
             #[cfg(target_arch = "x86_64")]
             AVX512 => is_x86_feature_detected!("avx512f"),
+            #[cfg(target_arch = "aarch64")]
+            Neon | NeonPair => is_aarch64_feature_detected!("neon"),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            RepMovsb => has_fast_string_operations(),
         }
     }
 }
 
+/// Whether the CPU reports enhanced `rep movsb`/`rep stosb` (ERMS) or fast short `rep movsb`
+/// (FSRM) in cpuid leaf 7
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn has_fast_string_operations() -> bool {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64 as x86;
+    const ERMS: u32 = 1 << 9;
+    const FSRM: u32 = 1 << 4;
+    let (max_leaf, _) = x86::__get_cpuid_max(0);
+    if max_leaf < 7 {
+        return false;
+    }
+    let features = x86::__cpuid_count(7, 0);
+    features.ebx & ERMS != 0 || features.edx & FSRM != 0
+}
+
 impl OperationStrategy {
+    /// Whether this strategy has a non-temporal variant that the CPU supports.
+    /// Only the SIMD strategies other than [`OperationStrategy::Neon`] do, the others ignore the
+    /// `non_temporal` flag.
     #[must_use]
-    pub const fn read_fn(&self) -> fn(&mut [u8]) {
+    pub fn supports_non_temporal(&self) -> bool {
         use OperationStrategy::*;
+        #[cfg(target_arch = "aarch64")]
+        use std::arch::is_aarch64_feature_detected;
         #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-        use core::arch::x86_64 as x86;
+        use std::arch::is_x86_feature_detected;
         match self {
-            Bytewise => read_by::<16, usize>,
-            Int32 => read_by::<16, u32>,
-            Int64 => read_by::<16, u64>,
-            Int128 => read_by::<16, u128>,
-            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-            SSE => |data| {
-                unsafe {
-                    x86::_mm_sfence();
-                }
-                for_each_aligned_value::<32, x86::__m128i, x86::__m128i>(data, |val| unsafe {
-                    x86::_mm_stream_load_si128(val.as_ptr())
-                });
+            Bytewise | Int32 | Int64 | Int128 | Libc | RustStd => false,
+            #[cfg(target_arch = "aarch64")]
+            Neon => false,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            RepMovsb | Prefetch => false,
+            #[cfg(target_arch = "aarch64")]
+            NeonPair => is_aarch64_feature_detected!("neon"),
+            // Streaming loads came with SSE4.1
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            SSE => is_x86_feature_detected!("sse4.1"),
+            #[cfg(target_arch = "x86_64")]
+            AVX2 => is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "x86_64")]
+            AVX512 => is_x86_feature_detected!("avx512f"),
+        }
+    }
+    /// Whether this strategy can run `operation`.
+    ///
+    /// The [`OperationStrategy::RepMovsb`], [`OperationStrategy::Libc`] and
+    /// [`OperationStrategy::RustStd`] baselines only copy and fill memory, so they can't run
+    /// reads or mixed passes: `rep lodsb` and `repe scasb` aren't among the fast string
+    /// operations, and neither libc nor the standard library has a routine that plainly reads a
+    /// buffer.
+    #[must_use]
+    pub const fn supports_operation(&self, operation: MemoryOperation) -> bool {
+        use OperationStrategy::*;
+        let reads = matches!(operation, MemoryOperation::Read | MemoryOperation::Mixed);
+        match self {
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            RepMovsb => !reads,
+            Libc | RustStd => !reads,
+            _ => true,
+        }
+    }
+    /// Reads every byte of a buffer, returning how many bytes were read.
+    /// With `non_temporal` the SIMD strategies use streaming loads.
+    ///
+    /// `None` for the strategies that can't read, see [`OperationStrategy::supports_operation`].
+    ///
+    /// [`OperationStrategy::Prefetch`] prefetches the default distance with [`PrefetchHint::T0`]
+    /// here, see [`OperationStrategy::prefetching_read_fn`] and
+    /// [`crate::DEFAULT_PREFETCH_DISTANCE`].
+    #[must_use]
+    pub const fn read_fn(&self, non_temporal: bool) -> Option<ReadFn> {
+        self.read_fn_with::<BlackBoxSink>(non_temporal)
+    }
+    /// [`OperationStrategy::read_fn`], handing every value it loads to `S`
+    #[must_use]
+    pub const fn read_fn_with<S: ReadSink>(&self, non_temporal: bool) -> Option<ReadFn> {
+        use OperationStrategy::*;
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        use core::arch::x86_64 as x86;
+        #[cfg(target_arch = "aarch64")]
+        use core::arch::{aarch64 as arm, asm};
+        let read: ReadFn = match (self, non_temporal) {
+            (Bytewise, _) => read_by::<16, usize, S>,
+            (Int32, _) => read_by::<16, u32, S>,
+            (Int64, _) => read_by::<16, u64, S>,
+            (Int128, _) => read_by::<16, u128, S>,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            (RepMovsb, _) => return None,
+            (Libc | RustStd, _) => return None,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            (Prefetch, _) => |data, len| unsafe {
+                prefetching_read::<{ x86::_MM_HINT_T0 }, S>(data, len, DEFAULT_PREFETCH_DISTANCE)
+            },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            (SSE, false) => |data, len| unsafe {
+                for_each_aligned_value::<32, x86::__m128i, ()>(
+                    data.cast_mut(),
+                    len,
+                    |val| S::consume(val, x86::_mm_load_si128(val)),
+                    |data, len| read_bytes::<S>(data, len),
+                )
             },
             #[cfg(target_arch = "x86_64")]
-            AVX2 => |data| {
-                unsafe {
-                    x86::_mm_sfence();
-                }
-                for_each_aligned_value::<64, x86::__m256i, x86::__m256i>(data, |val| unsafe {
-                    x86::_mm256_stream_load_si256(val.as_ptr())
-                });
+            (AVX2, false) => |data, len| unsafe {
+                for_each_aligned_value::<64, x86::__m256i, ()>(
+                    data.cast_mut(),
+                    len,
+                    |val| S::consume(val, x86::_mm256_load_si256(val)),
+                    |data, len| read_bytes::<S>(data, len),
+                )
             },
             #[cfg(target_arch = "x86_64")]
-            AVX512 => |data| {
-                unsafe {
-                    x86::_mm_sfence();
-                }
-                for_each_aligned_value::<64, x86::__m512i, x86::__m512i>(data, |val| unsafe {
-                    x86::_mm512_stream_load_si512(val.as_ptr())
-                });
+            (AVX512, false) => |data, len| unsafe {
+                for_each_aligned_value::<64, x86::__m512i, ()>(
+                    data.cast_mut(),
+                    len,
+                    |val| S::consume(val, x86::_mm512_load_si512(val)),
+                    |data, len| read_bytes::<S>(data, len),
+                )
             },
-        }
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            (SSE, true) => |data, len| unsafe {
+                x86::_mm_sfence();
+                for_each_aligned_value::<32, x86::__m128i, ()>(
+                    data.cast_mut(),
+                    len,
+                    |val| S::consume(val, x86::_mm_stream_load_si128(val)),
+                    |data, len| read_bytes::<S>(data, len),
+                )
+            },
+            #[cfg(target_arch = "x86_64")]
+            (AVX2, true) => |data, len| unsafe {
+                x86::_mm_sfence();
+                for_each_aligned_value::<64, x86::__m256i, ()>(
+                    data.cast_mut(),
+                    len,
+                    |val| S::consume(val, x86::_mm256_stream_load_si256(val)),
+                    |data, len| read_bytes::<S>(data, len),
+                )
+            },
+            #[cfg(target_arch = "x86_64")]
+            (AVX512, true) => |data, len| unsafe {
+                x86::_mm_sfence();
+                for_each_aligned_value::<64, x86::__m512i, ()>(
+                    data.cast_mut(),
+                    len,
+                    |val| S::consume(val, x86::_mm512_stream_load_si512(val)),
+                    |data, len| read_bytes::<S>(data, len),
+                )
+            },
+            #[cfg(target_arch = "aarch64")]
+            (Neon, _) => |data, len| unsafe {
+                for_each_aligned_value::<32, arm::uint8x16_t, ()>(
+                    data.cast_mut(),
+                    len,
+                    |val| S::consume(val, arm::vld1q_u8(val.cast())),
+                    |data, len| read_bytes::<S>(data, len),
+                )
+            },
+            #[cfg(target_arch = "aarch64")]
+            (NeonPair, false) => |data, len| unsafe {
+                for_each_aligned_value::<16, RegisterPair, ()>(
+                    data.cast_mut(),
+                    len,
+                    |pair| {
+                        let (a, b): (arm::uint8x16_t, arm::uint8x16_t);
+                        asm!(
+                            "ldp {a:q}, {b:q}, [{pair}]",
+                            pair = in(reg) pair,
+                            a = out(vreg) a,
+                            b = out(vreg) b,
+                            options(nostack, readonly, preserves_flags),
+                        );
+                        S::consume(pair.cast::<[arm::uint8x16_t; 2]>(), [a, b]);
+                    },
+                    |data, len| read_bytes::<S>(data, len),
+                )
+            },
+            #[cfg(target_arch = "aarch64")]
+            (NeonPair, true) => |data, len| unsafe {
+                for_each_aligned_value::<16, RegisterPair, ()>(
+                    data.cast_mut(),
+                    len,
+                    |pair| {
+                        let (a, b): (arm::uint8x16_t, arm::uint8x16_t);
+                        asm!(
+                            "ldnp {a:q}, {b:q}, [{pair}]",
+                            pair = in(reg) pair,
+                            a = out(vreg) a,
+                            b = out(vreg) b,
+                            options(nostack, readonly, preserves_flags),
+                        );
+                        S::consume(pair.cast::<[arm::uint8x16_t; 2]>(), [a, b]);
+                    },
+                    |data, len| read_bytes::<S>(data, len),
+                )
+            },
+        };
+        Some(read)
     }
+    /// Fills a buffer with [`WRITE_PATTERN`], returning how many bytes were written.
+    /// With `non_temporal` the SIMD strategies use streaming stores.
     #[must_use]
-    pub const fn write_fn(&self) -> fn(&mut [u8]) {
+    pub const fn write_fn(&self, non_temporal: bool) -> WriteFn {
         use OperationStrategy::*;
         #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
         use core::arch::x86_64 as x86;
-        match self {
-            Bytewise => |data| unsafe {
-                data.as_mut_ptr().write_bytes(0xAA, data.len());
+        #[cfg(target_arch = "aarch64")]
+        use core::arch::{aarch64 as arm, asm};
+        match (self, non_temporal) {
+            (Bytewise, _) => |data, len| unsafe {
+                data.write_bytes(WRITE_PATTERN, len);
+                len
             },
-            Int32 => |data| unsafe { write_by::<16, u32>(data, 0xAAAAAAAA) },
-            Int64 => |data| unsafe { write_by::<16, u64>(data, 0xAAAAAAAAAAAAAAAA) },
-            Int128 => {
-                |data| unsafe { write_by::<16, u128>(data, 0xAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA) }
-            }
+            (RustStd, _) => |data, len| {
+                unsafe { core::slice::from_raw_parts_mut(data, len) }.fill(WRITE_PATTERN);
+                len
+            },
+            (Int32, _) => |data, len| unsafe { write_by::<16, u32>(data, len, 0xAAAAAAAA) },
+            (Int64, _) => |data, len| unsafe { write_by::<16, u64>(data, len, 0xAAAAAAAAAAAAAAAA) },
+            (Int128, _) => |data, len| unsafe {
+                write_by::<16, u128>(data, len, 0xAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA)
+            },
+            #[cfg(target_arch = "x86")]
+            (RepMovsb, _) => |data, len| unsafe {
+                core::arch::asm!(
+                    "rep stosb",
+                    inout("ecx") len => _,
+                    inout("edi") data => _,
+                    in("al") WRITE_PATTERN,
+                    options(nostack, preserves_flags),
+                );
+                len
+            },
+            #[cfg(target_arch = "x86_64")]
+            (RepMovsb, _) => |data, len| unsafe {
+                core::arch::asm!(
+                    "rep stosb",
+                    inout("rcx") len => _,
+                    inout("rdi") data => _,
+                    in("al") WRITE_PATTERN,
+                    options(nostack, preserves_flags),
+                );
+                len
+            },
+            (Libc, _) => |data, len| unsafe {
+                libc::memset(data.cast(), WRITE_PATTERN.into(), len);
+                len
+            },
+            // Writes gain nothing from prefetching
             #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-            SSE => |data| unsafe {
-                let value = x86::_mm_set1_epi8(0xAA_u8 as i8);
-                for_each_aligned_value::<32, x86::__m128i, ()>(data, |slot| {
-                    x86::_mm_stream_si128(slot.as_mut_ptr(), value)
-                });
+            (SSE, false) | (Prefetch, _) => |data, len| unsafe {
+                let value = x86::_mm_set1_epi8(WRITE_PATTERN as i8);
+                for_each_aligned_value::<32, x86::__m128i, ()>(
+                    data,
+                    len,
+                    |slot| x86::_mm_store_si128(slot, value),
+                    |data, len| write_bytes(data, len),
+                )
+            },
+            #[cfg(target_arch = "x86_64")]
+            (AVX2, false) => |data, len| unsafe {
+                let value = x86::_mm256_set1_epi8(WRITE_PATTERN as i8);
+                for_each_aligned_value::<64, x86::__m256i, ()>(
+                    data,
+                    len,
+                    |slot| x86::_mm256_store_si256(slot, value),
+                    |data, len| write_bytes(data, len),
+                )
+            },
+            #[cfg(target_arch = "x86_64")]
+            (AVX512, false) => |data, len| unsafe {
+                let value = x86::_mm512_set1_epi8(WRITE_PATTERN as i8);
+                for_each_aligned_value::<64, x86::__m512i, ()>(
+                    data,
+                    len,
+                    |slot| x86::_mm512_store_si512(slot, value),
+                    |data, len| write_bytes(data, len),
+                )
+            },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            (SSE, true) => |data, len| unsafe {
+                let value = x86::_mm_set1_epi8(WRITE_PATTERN as i8);
+                let written = for_each_aligned_value::<32, x86::__m128i, ()>(
+                    data,
+                    len,
+                    |slot| x86::_mm_stream_si128(slot, value),
+                    |data, len| write_bytes(data, len),
+                );
                 x86::_mm_sfence();
+                written
             },
             #[cfg(target_arch = "x86_64")]
-            AVX2 => |data| unsafe {
-                let value = x86::_mm256_set1_epi8(0xAA_u8 as i8);
-                for_each_aligned_value::<64, x86::__m256i, ()>(data, |slot| {
-                    x86::_mm256_stream_si256(slot.as_mut_ptr(), value);
-                });
+            (AVX2, true) => |data, len| unsafe {
+                let value = x86::_mm256_set1_epi8(WRITE_PATTERN as i8);
+                let written = for_each_aligned_value::<64, x86::__m256i, ()>(
+                    data,
+                    len,
+                    |slot| x86::_mm256_stream_si256(slot, value),
+                    |data, len| write_bytes(data, len),
+                );
                 x86::_mm_sfence();
+                written
             },
             #[cfg(target_arch = "x86_64")]
-            AVX512 => |data| unsafe {
-                let value = x86::_mm512_set1_epi8(0xAA_u8 as i8);
-                for_each_aligned_value::<64, x86::__m512i, ()>(data, |slot| {
-                    x86::_mm512_stream_si512(slot.as_mut_ptr(), value);
-                });
+            (AVX512, true) => |data, len| unsafe {
+                let value = x86::_mm512_set1_epi8(WRITE_PATTERN as i8);
+                let written = for_each_aligned_value::<64, x86::__m512i, ()>(
+                    data,
+                    len,
+                    |slot| x86::_mm512_stream_si512(slot, value),
+                    |data, len| write_bytes(data, len),
+                );
                 x86::_mm_sfence();
+                written
+            },
+            #[cfg(target_arch = "aarch64")]
+            (Neon, _) => |data, len| unsafe {
+                let value = arm::vdupq_n_u8(WRITE_PATTERN);
+                for_each_aligned_value::<32, arm::uint8x16_t, ()>(
+                    data,
+                    len,
+                    |slot| arm::vst1q_u8(slot.cast(), value),
+                    |data, len| write_bytes(data, len),
+                )
+            },
+            #[cfg(target_arch = "aarch64")]
+            (NeonPair, false) => |data, len| unsafe {
+                let value = arm::vdupq_n_u8(WRITE_PATTERN);
+                for_each_aligned_value::<16, RegisterPair, ()>(
+                    data,
+                    len,
+                    |slot| {
+                        asm!(
+                            "stp {value:q}, {value:q}, [{slot}]",
+                            slot = in(reg) slot,
+                            value = in(vreg) value,
+                            options(nostack, preserves_flags),
+                        );
+                    },
+                    |data, len| write_bytes(data, len),
+                )
+            },
+            #[cfg(target_arch = "aarch64")]
+            (NeonPair, true) => |data, len| unsafe {
+                let value = arm::vdupq_n_u8(WRITE_PATTERN);
+                for_each_aligned_value::<16, RegisterPair, ()>(
+                    data,
+                    len,
+                    |slot| {
+                        asm!(
+                            "stnp {value:q}, {value:q}, [{slot}]",
+                            slot = in(reg) slot,
+                            value = in(vreg) value,
+                            options(nostack, preserves_flags),
+                        );
+                    },
+                    |data, len| write_bytes(data, len),
+                )
             },
         }
     }
+    /// Copies `len` bytes between two buffers, returning how many bytes were copied.
+    /// With `non_temporal` the SIMD strategies use streaming stores, and streaming loads where the
+    /// source is aligned to the vector width.
+    ///
+    /// [`OperationStrategy::Prefetch`] prefetches the default distance with [`PrefetchHint::T0`]
+    /// here, see [`OperationStrategy::prefetching_copy_fn`] and
+    /// [`crate::DEFAULT_PREFETCH_DISTANCE`].
     #[must_use]
-    pub fn copy_nonoverlapping_fn(&self) -> unsafe fn(*const u8, *mut u8, usize) {
+    pub fn copy_nonoverlapping_fn(
+        &self,
+        non_temporal: bool,
+    ) -> unsafe fn(*const u8, *mut u8, usize) -> usize {
         use OperationStrategy::*;
         #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
         use core::arch::x86_64 as x86;
-        use core::mem::size_of;
-        match self {
-            Bytewise => |from, to, len| unsafe {
+        #[cfg(target_arch = "aarch64")]
+        use core::arch::{aarch64 as arm, asm};
+        match (self, non_temporal) {
+            (Bytewise, _) => |from, to, len| unsafe {
                 to.copy_from_nonoverlapping(from, len);
+                len
             },
-            Int32 => |from, to, len| unsafe {
-                type Register = u32;
-                for_each_idx_chunked::<16, ()>(len / size_of::<Register>(), |idx| {
-                    let from = from.cast::<Register>().add(idx);
-                    let to = to.cast::<Register>().add(idx);
-                    let val = core::ptr::read(from);
-                    core::ptr::write(to, val);
+            (RustStd, _) => |from, to, len| unsafe {
+                let from = core::slice::from_raw_parts(from, len);
+                core::slice::from_raw_parts_mut(to, len).copy_from_slice(from);
+                len
+            },
+            #[cfg(target_arch = "x86")]
+            (RepMovsb, _) => |from, to, len| unsafe {
+                core::arch::asm!(
+                    "rep movsb",
+                    inout("ecx") len => _,
+                    inout("esi") from => _,
+                    inout("edi") to => _,
+                    options(nostack, preserves_flags),
+                );
+                len
+            },
+            #[cfg(target_arch = "x86_64")]
+            (RepMovsb, _) => |from, to, len| unsafe {
+                core::arch::asm!(
+                    "rep movsb",
+                    inout("rcx") len => _,
+                    inout("rsi") from => _,
+                    inout("rdi") to => _,
+                    options(nostack, preserves_flags),
+                );
+                len
+            },
+            (Libc, _) => |from, to, len| unsafe {
+                libc::memcpy(to.cast(), from.cast(), len);
+                len
+            },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            (Prefetch, _) => |from, to, len| unsafe {
+                prefetching_copy::<{ x86::_MM_HINT_T0 }>(from, to, len, DEFAULT_PREFETCH_DISTANCE)
+            },
+            (Int32, _) => |from, to, len| unsafe {
+                copy_by::<16, u32>(from, to, len, |from, to| {
+                    let val = from.read_unaligned();
+                    to.write(val);
                     black_box(val);
-                });
+                })
             },
-            Int64 => |from, to, len| unsafe {
-                type Register = u64;
-                for_each_idx_chunked::<16, ()>(len / size_of::<Register>(), |idx| {
-                    let from = from.cast::<Register>().add(idx);
-                    let to = to.cast::<Register>().add(idx);
-                    let val = core::ptr::read(from);
-                    core::ptr::write(to, val);
+            (Int64, _) => |from, to, len| unsafe {
+                copy_by::<16, u64>(from, to, len, |from, to| {
+                    let val = from.read_unaligned();
+                    to.write(val);
                     black_box(val);
-                });
+                })
             },
-            Int128 => |from, to, len| unsafe {
-                type Register = u128;
-                for_each_idx_chunked::<16, ()>(len / size_of::<Register>(), |idx| {
-                    let from = from.cast::<Register>().add(idx);
-                    let to = to.cast::<Register>().add(idx);
-                    let val = core::ptr::read(from);
-                    core::ptr::write(to, val);
+            (Int128, _) => |from, to, len| unsafe {
+                copy_by::<16, u128>(from, to, len, |from, to| {
+                    let val = from.read_unaligned();
+                    to.write(val);
                     black_box(val);
-                });
+                })
             },
             #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-            SSE => |from, to, len| unsafe {
-                type Register = x86::__m128i;
+            (SSE, false) => |from, to, len| unsafe {
+                copy_by::<32, x86::__m128i>(from, to, len, |from, to| {
+                    x86::_mm_store_si128(to, x86::_mm_loadu_si128(from));
+                })
+            },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            (SSE, true) => |from, to, len| unsafe {
                 x86::_mm_sfence();
-                for_each_idx_chunked::<32, ()>(len / size_of::<Register>(), |idx| {
-                    let from = from.cast::<Register>().add(idx);
-                    let to = to.cast::<Register>().add(idx);
-                    let val = x86::_mm_stream_load_si128(from);
+                let copied = copy_by::<32, x86::__m128i>(from, to, len, |from, to| {
+                    let val = if from.is_aligned() {
+                        x86::_mm_stream_load_si128(from)
+                    } else {
+                        x86::_mm_loadu_si128(from)
+                    };
                     x86::_mm_stream_si128(to, val);
                 });
                 x86::_mm_sfence();
+                copied
             },
             #[cfg(target_arch = "x86_64")]
-            AVX2 => |from, to, len| unsafe {
-                type Register = x86::__m256i;
+            (AVX2, false) => |from, to, len| unsafe {
+                copy_by::<64, x86::__m256i>(from, to, len, |from, to| {
+                    x86::_mm256_store_si256(to, x86::_mm256_loadu_si256(from));
+                })
+            },
+            #[cfg(target_arch = "x86_64")]
+            (AVX2, true) => |from, to, len| unsafe {
                 x86::_mm_sfence();
-                for_each_idx_chunked::<64, ()>(len / size_of::<Register>(), |idx| {
-                    let from = from.cast::<Register>().add(idx);
-                    let to = to.cast::<Register>().add(idx);
-                    let val = x86::_mm256_stream_load_si256(from);
+                let copied = copy_by::<64, x86::__m256i>(from, to, len, |from, to| {
+                    let val = if from.is_aligned() {
+                        x86::_mm256_stream_load_si256(from)
+                    } else {
+                        x86::_mm256_loadu_si256(from)
+                    };
                     x86::_mm256_stream_si256(to, val);
                 });
                 x86::_mm_sfence();
+                copied
             },
             #[cfg(target_arch = "x86_64")]
-            AVX512 => |from, to, len| unsafe {
-                type Register = x86::__m512i;
+            (AVX512, false) => |from, to, len| unsafe {
+                copy_by::<64, x86::__m512i>(from, to, len, |from, to| {
+                    x86::_mm512_store_si512(to.cast(), x86::_mm512_loadu_si512(from.cast()));
+                })
+            },
+            #[cfg(target_arch = "x86_64")]
+            (AVX512, true) => |from, to, len| unsafe {
                 x86::_mm_sfence();
-                for_each_idx_chunked::<64, ()>(len / size_of::<Register>(), |idx| {
-                    let from = from.cast::<Register>().add(idx);
-                    let to = to.cast::<Register>().add(idx);
-                    let val = x86::_mm512_stream_load_si512(from);
+                let copied = copy_by::<64, x86::__m512i>(from, to, len, |from, to| {
+                    let val = if from.is_aligned() {
+                        x86::_mm512_stream_load_si512(from)
+                    } else {
+                        x86::_mm512_loadu_si512(from.cast())
+                    };
                     x86::_mm512_stream_si512(to, val);
                 });
                 x86::_mm_sfence();
+                copied
+            },
+            #[cfg(target_arch = "aarch64")]
+            (Neon, _) => |from, to, len| unsafe {
+                copy_by::<32, arm::uint8x16_t>(from, to, len, |from, to| {
+                    arm::vst1q_u8(to.cast(), arm::vld1q_u8(from.cast()));
+                })
+            },
+            #[cfg(target_arch = "aarch64")]
+            (NeonPair, false) => |from, to, len| unsafe {
+                copy_by::<16, RegisterPair>(from, to, len, |from, to| {
+                    asm!(
+                        "ldp {a:q}, {b:q}, [{from}]",
+                        "stp {a:q}, {b:q}, [{to}]",
+                        from = in(reg) from,
+                        to = in(reg) to,
+                        a = out(vreg) _,
+                        b = out(vreg) _,
+                        options(nostack, preserves_flags),
+                    );
+                })
             },
+            #[cfg(target_arch = "aarch64")]
+            (NeonPair, true) => |from, to, len| unsafe {
+                copy_by::<16, RegisterPair>(from, to, len, |from, to| {
+                    asm!(
+                        "ldnp {a:q}, {b:q}, [{from}]",
+                        "stnp {a:q}, {b:q}, [{to}]",
+                        from = in(reg) from,
+                        to = in(reg) to,
+                        a = out(vreg) _,
+                        b = out(vreg) _,
+                        options(nostack, preserves_flags),
+                    );
+                })
+            },
+        }
+    }
+    /// Reads every byte of a buffer like [`OperationStrategy::read_fn`], but a value at a time
+    /// counting from the start of the buffer instead of realigning to the width of the values
+    /// first, so that misaligned buffers take unaligned and cache line splitting loads.
+    /// There are no unaligned streaming loads, so this has no non-temporal variant.
+    ///
+    /// [`OperationStrategy::Bytewise`] reads like it always does, and
+    /// [`OperationStrategy::Prefetch`] reads like [`OperationStrategy::SSE`]. `None` for the
+    /// strategies that can't read, like [`OperationStrategy::read_fn`].
+    #[must_use]
+    pub const fn unaligned_read_fn(&self) -> Option<ReadFn> {
+        self.unaligned_read_fn_with::<BlackBoxSink>()
+    }
+    /// [`OperationStrategy::unaligned_read_fn`], handing every value it loads to `S`
+    #[must_use]
+    pub const fn unaligned_read_fn_with<S: ReadSink>(&self) -> Option<ReadFn> {
+        use OperationStrategy::*;
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        use core::arch::x86_64 as x86;
+        #[cfg(target_arch = "aarch64")]
+        use core::arch::{aarch64 as arm, asm};
+        let read: ReadFn = match self {
+            Bytewise | Libc | RustStd => return self.read_fn_with::<S>(false),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            RepMovsb => return self.read_fn_with::<S>(false),
+            Int32 => read_unaligned_by::<16, u32, S>,
+            Int64 => read_unaligned_by::<16, u64, S>,
+            Int128 => read_unaligned_by::<16, u128, S>,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            SSE | Prefetch => |data, len| unsafe {
+                for_each_unaligned_value::<32, x86::__m128i, ()>(
+                    data.cast_mut(),
+                    len,
+                    |val| S::consume(val.cast_const(), x86::_mm_loadu_si128(val)),
+                    |data, len| read_bytes::<S>(data, len),
+                )
+            },
+            #[cfg(target_arch = "x86_64")]
+            AVX2 => |data, len| unsafe {
+                for_each_unaligned_value::<64, x86::__m256i, ()>(
+                    data.cast_mut(),
+                    len,
+                    |val| S::consume(val.cast_const(), x86::_mm256_loadu_si256(val)),
+                    |data, len| read_bytes::<S>(data, len),
+                )
+            },
+            #[cfg(target_arch = "x86_64")]
+            AVX512 => |data, len| unsafe {
+                for_each_unaligned_value::<64, x86::__m512i, ()>(
+                    data.cast_mut(),
+                    len,
+                    |val| S::consume(val.cast_const(), x86::_mm512_loadu_si512(val.cast())),
+                    |data, len| read_bytes::<S>(data, len),
+                )
+            },
+            #[cfg(target_arch = "aarch64")]
+            Neon => |data, len| unsafe {
+                for_each_unaligned_value::<32, arm::uint8x16_t, ()>(
+                    data.cast_mut(),
+                    len,
+                    |val| S::consume(val.cast_const(), arm::vld1q_u8(val.cast())),
+                    |data, len| read_bytes::<S>(data, len),
+                )
+            },
+            #[cfg(target_arch = "aarch64")]
+            NeonPair => |data, len| unsafe {
+                for_each_unaligned_value::<16, RegisterPair, ()>(
+                    data.cast_mut(),
+                    len,
+                    |pair| {
+                        let (a, b): (arm::uint8x16_t, arm::uint8x16_t);
+                        asm!(
+                            "ldp {a:q}, {b:q}, [{pair}]",
+                            pair = in(reg) pair,
+                            a = out(vreg) a,
+                            b = out(vreg) b,
+                            options(nostack, readonly, preserves_flags),
+                        );
+                        S::consume(pair.cast_const().cast::<[arm::uint8x16_t; 2]>(), [a, b]);
+                    },
+                    |data, len| read_bytes::<S>(data, len),
+                )
+            },
+        };
+        Some(read)
+    }
+    /// Fills a buffer with [`WRITE_PATTERN`] like [`OperationStrategy::write_fn`], but a value at
+    /// a time counting from the start of the buffer, see
+    /// [`OperationStrategy::unaligned_read_fn`].
+    #[must_use]
+    pub const fn unaligned_write_fn(&self) -> WriteFn {
+        use OperationStrategy::*;
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        use core::arch::x86_64 as x86;
+        #[cfg(target_arch = "aarch64")]
+        use core::arch::{aarch64 as arm, asm};
+        match self {
+            Bytewise | Libc | RustStd => self.write_fn(false),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            RepMovsb => self.write_fn(false),
+            Int32 => |data, len| unsafe { write_unaligned_by::<16, u32>(data, len, 0xAAAAAAAA) },
+            Int64 => {
+                |data, len| unsafe { write_unaligned_by::<16, u64>(data, len, 0xAAAAAAAAAAAAAAAA) }
+            }
+            Int128 => |data, len| unsafe {
+                write_unaligned_by::<16, u128>(data, len, 0xAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA)
+            },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            SSE | Prefetch => |data, len| unsafe {
+                let value = x86::_mm_set1_epi8(WRITE_PATTERN as i8);
+                for_each_unaligned_value::<32, x86::__m128i, ()>(
+                    data,
+                    len,
+                    |slot| x86::_mm_storeu_si128(slot, value),
+                    |data, len| write_bytes(data, len),
+                )
+            },
+            #[cfg(target_arch = "x86_64")]
+            AVX2 => |data, len| unsafe {
+                let value = x86::_mm256_set1_epi8(WRITE_PATTERN as i8);
+                for_each_unaligned_value::<64, x86::__m256i, ()>(
+                    data,
+                    len,
+                    |slot| x86::_mm256_storeu_si256(slot, value),
+                    |data, len| write_bytes(data, len),
+                )
+            },
+            #[cfg(target_arch = "x86_64")]
+            AVX512 => |data, len| unsafe {
+                let value = x86::_mm512_set1_epi8(WRITE_PATTERN as i8);
+                for_each_unaligned_value::<64, x86::__m512i, ()>(
+                    data,
+                    len,
+                    |slot| x86::_mm512_storeu_si512(slot.cast(), value),
+                    |data, len| write_bytes(data, len),
+                )
+            },
+            #[cfg(target_arch = "aarch64")]
+            Neon => |data, len| unsafe {
+                let value = arm::vdupq_n_u8(WRITE_PATTERN);
+                for_each_unaligned_value::<32, arm::uint8x16_t, ()>(
+                    data,
+                    len,
+                    |slot| arm::vst1q_u8(slot.cast(), value),
+                    |data, len| write_bytes(data, len),
+                )
+            },
+            #[cfg(target_arch = "aarch64")]
+            NeonPair => |data, len| unsafe {
+                let value = arm::vdupq_n_u8(WRITE_PATTERN);
+                for_each_unaligned_value::<16, RegisterPair, ()>(
+                    data,
+                    len,
+                    |slot| {
+                        asm!(
+                            "stp {value:q}, {value:q}, [{slot}]",
+                            slot = in(reg) slot,
+                            value = in(vreg) value,
+                            options(nostack, preserves_flags),
+                        );
+                    },
+                    |data, len| write_bytes(data, len),
+                )
+            },
+        }
+    }
+    /// Copies `len` bytes between two buffers like [`OperationStrategy::copy_nonoverlapping_fn`],
+    /// but a value at a time counting from the start of both buffers, see
+    /// [`OperationStrategy::unaligned_read_fn`].
+    #[must_use]
+    pub fn unaligned_copy_fn(&self) -> unsafe fn(*const u8, *mut u8, usize) -> usize {
+        use OperationStrategy::*;
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        use core::arch::x86_64 as x86;
+        #[cfg(target_arch = "aarch64")]
+        use core::arch::{aarch64 as arm, asm};
+        match self {
+            Bytewise | Libc | RustStd => self.copy_nonoverlapping_fn(false),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            RepMovsb => self.copy_nonoverlapping_fn(false),
+            Int32 => |from, to, len| unsafe {
+                copy_unaligned_by::<16, u32>(from, to, len, |from, to| {
+                    let val = from.read_unaligned();
+                    to.write_unaligned(val);
+                    black_box(val);
+                })
+            },
+            Int64 => |from, to, len| unsafe {
+                copy_unaligned_by::<16, u64>(from, to, len, |from, to| {
+                    let val = from.read_unaligned();
+                    to.write_unaligned(val);
+                    black_box(val);
+                })
+            },
+            Int128 => |from, to, len| unsafe {
+                copy_unaligned_by::<16, u128>(from, to, len, |from, to| {
+                    let val = from.read_unaligned();
+                    to.write_unaligned(val);
+                    black_box(val);
+                })
+            },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            SSE | Prefetch => |from, to, len| unsafe {
+                copy_unaligned_by::<32, x86::__m128i>(from, to, len, |from, to| {
+                    x86::_mm_storeu_si128(to, x86::_mm_loadu_si128(from));
+                })
+            },
+            #[cfg(target_arch = "x86_64")]
+            AVX2 => |from, to, len| unsafe {
+                copy_unaligned_by::<64, x86::__m256i>(from, to, len, |from, to| {
+                    x86::_mm256_storeu_si256(to, x86::_mm256_loadu_si256(from));
+                })
+            },
+            #[cfg(target_arch = "x86_64")]
+            AVX512 => |from, to, len| unsafe {
+                copy_unaligned_by::<64, x86::__m512i>(from, to, len, |from, to| {
+                    x86::_mm512_storeu_si512(to.cast(), x86::_mm512_loadu_si512(from.cast()));
+                })
+            },
+            #[cfg(target_arch = "aarch64")]
+            Neon => |from, to, len| unsafe {
+                copy_unaligned_by::<32, arm::uint8x16_t>(from, to, len, |from, to| {
+                    arm::vst1q_u8(to.cast(), arm::vld1q_u8(from.cast()));
+                })
+            },
+            #[cfg(target_arch = "aarch64")]
+            NeonPair => |from, to, len| unsafe {
+                copy_unaligned_by::<16, RegisterPair>(from, to, len, |from, to| {
+                    asm!(
+                        "ldp {a:q}, {b:q}, [{from}]",
+                        "stp {a:q}, {b:q}, [{to}]",
+                        from = in(reg) from,
+                        to = in(reg) to,
+                        a = out(vreg) _,
+                        b = out(vreg) _,
+                        options(nostack, preserves_flags),
+                    );
+                })
+            },
+        }
+    }
+    /// Whether this strategy prefetches ahead of its reads and copies, see
+    /// `Config::prefetch_distance`
+    #[must_use]
+    pub const fn is_prefetching(&self) -> bool {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        return matches!(self, OperationStrategy::Prefetch);
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+        false
+    }
+    /// Reads every byte of a buffer like [`OperationStrategy::read_fn`], prefetching with `hint`
+    /// the number of bytes ahead of every cache line given as the second argument.
+    /// `None` unless this strategy [prefetches](OperationStrategy::is_prefetching).
+    #[must_use]
+    pub fn prefetching_read_fn(&self, hint: PrefetchHint) -> Option<PrefetchingReadFn> {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64 as x86;
+            if !self.is_prefetching() {
+                return None;
+            }
+            Some(match hint {
+                PrefetchHint::T0 => prefetching_read::<{ x86::_MM_HINT_T0 }, BlackBoxSink>,
+                PrefetchHint::T1 => prefetching_read::<{ x86::_MM_HINT_T1 }, BlackBoxSink>,
+                PrefetchHint::T2 => prefetching_read::<{ x86::_MM_HINT_T2 }, BlackBoxSink>,
+                PrefetchHint::Nta => prefetching_read::<{ x86::_MM_HINT_NTA }, BlackBoxSink>,
+            })
+        }
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+        {
+            let _ = hint;
+            None
+        }
+    }
+    /// Copies `len` bytes between two buffers like [`OperationStrategy::copy_nonoverlapping_fn`],
+    /// prefetching the source with `hint` the number of bytes ahead of every cache line given as
+    /// the last argument. `None` unless this strategy
+    /// [prefetches](OperationStrategy::is_prefetching).
+    #[must_use]
+    pub fn prefetching_copy_fn(&self, hint: PrefetchHint) -> Option<PrefetchingCopyFn> {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64 as x86;
+            if !self.is_prefetching() {
+                return None;
+            }
+            Some(match hint {
+                PrefetchHint::T0 => prefetching_copy::<{ x86::_MM_HINT_T0 }>,
+                PrefetchHint::T1 => prefetching_copy::<{ x86::_MM_HINT_T1 }>,
+                PrefetchHint::T2 => prefetching_copy::<{ x86::_MM_HINT_T2 }>,
+                PrefetchHint::Nta => prefetching_copy::<{ x86::_MM_HINT_NTA }>,
+            })
+        }
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+        {
+            let _ = hint;
+            None
+        }
+    }
+    /// The STREAM kernel implementing `operation` with this strategy.
+    /// The integer and baseline strategies all use the scalar kernels, with `non_temporal` the
+    /// SIMD ones write `a` with streaming stores.
+    ///
+    /// # Panics
+    /// If `operation` is not a STREAM kernel
+    #[must_use]
+    pub fn stream_kernel_fn(
+        &self,
+        operation: MemoryOperation,
+        non_temporal: bool,
+    ) -> StreamKernelFn {
+        use OperationStrategy::*;
+        macro_rules! kernel {
+            ($module:path $(, $non_temporal:literal)?) => {{
+                use $module as kernels;
+                match operation {
+                    MemoryOperation::Scale => kernels::scale$(::<$non_temporal>)? as StreamKernelFn,
+                    MemoryOperation::Add => kernels::add$(::<$non_temporal>)?,
+                    MemoryOperation::Triad => kernels::triad$(::<$non_temporal>)?,
+                    MemoryOperation::Read
+                    | MemoryOperation::Write
+                    | MemoryOperation::Copy
+                    | MemoryOperation::Mixed => {
+                        panic!("{operation:?} is not a STREAM kernel")
+                    }
+                }
+            }};
+        }
+        match self {
+            Bytewise | Int32 | Int64 | Int128 | Libc | RustStd => kernel!(stream::scalar),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            RepMovsb => kernel!(stream::scalar),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            SSE if non_temporal => kernel!(stream::sse, true),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            SSE | Prefetch => kernel!(stream::sse, false),
+            #[cfg(target_arch = "x86_64")]
+            AVX2 if non_temporal => kernel!(stream::avx2, true),
+            #[cfg(target_arch = "x86_64")]
+            AVX2 => kernel!(stream::avx2, false),
+            #[cfg(target_arch = "x86_64")]
+            AVX512 if non_temporal => kernel!(stream::avx512, true),
+            #[cfg(target_arch = "x86_64")]
+            AVX512 => kernel!(stream::avx512, false),
+            #[cfg(target_arch = "aarch64")]
+            Neon => kernel!(stream::neon, false),
+            #[cfg(target_arch = "aarch64")]
+            NeonPair => {
+                if non_temporal {
+                    kernel!(stream::neon, true)
+                } else {
+                    kernel!(stream::neon, false)
+                }
+            }
+        }
+    }
+}
+
+impl Config {
+    /// Whether the kernels use streaming loads and stores, `non_temporal` if `strategy` supports
+    /// them
+    #[must_use]
+    pub fn uses_non_temporal(&self) -> bool {
+        self.non_temporal && self.strategy.supports_non_temporal()
+    }
+    /// The read of `strategy`, prefetching `prefetch_distance` bytes ahead with `prefetch_hint` if
+    /// the strategy prefetches, or the unaligned one if `misalignment` is set
+    ///
+    /// The returned closure takes a buffer like a [`ReadFn`] and must only be called with a pointer
+    /// valid for reads of the given length.
+    ///
+    /// # Panics
+    /// When called if `strategy` can't read, see [`OperationStrategy::supports_operation`]
+    pub(crate) fn read_fn(&self) -> impl Fn(*const u8, usize) -> usize + use<> {
+        let read = if self.misalignment == 0 {
+            self.strategy.read_fn(self.uses_non_temporal())
+        } else {
+            self.strategy.unaligned_read_fn()
+        };
+        let prefetching_read = self.strategy.prefetching_read_fn(self.prefetch_hint);
+        let distance = self.prefetch_distance;
+        let strategy = self.strategy;
+        move |data, len| match (prefetching_read, read) {
+            (Some(prefetching_read), _) => unsafe { prefetching_read(data, len, distance) },
+            (None, Some(read)) => unsafe { read(data, len) },
+            (None, None) => panic!("{} can't read memory", strategy.as_str()),
+        }
+    }
+    /// The write of `strategy`, the unaligned one if `misalignment` is set
+    pub(crate) fn write_fn(&self) -> WriteFn {
+        if self.misalignment == 0 {
+            self.strategy.write_fn(self.uses_non_temporal())
+        } else {
+            self.strategy.unaligned_write_fn()
+        }
+    }
+    /// The copy of `strategy`, the unaligned one if `misalignment` is set. The prefetching
+    /// strategies copy with [`OperationStrategy::prefetching_copy_fn`] instead.
+    pub(crate) fn copy_fn(&self) -> unsafe fn(*const u8, *mut u8, usize) -> usize {
+        if self.misalignment == 0 {
+            self.strategy
+                .copy_nonoverlapping_fn(self.uses_non_temporal())
+        } else {
+            self.strategy.unaligned_copy_fn()
         }
     }
 }
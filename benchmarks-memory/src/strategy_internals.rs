@@ -1,12 +1,15 @@
 use seq_macro::seq;
-use std::{hint::black_box, mem::MaybeUninit};
+use std::hint::black_box;
+
+/// The byte every write strategy fills memory with
+pub const WRITE_PATTERN: u8 = 0xAA;
 
 pub fn for_each_idx_chunked<const MAX_CHUNK: usize, U>(len: usize, mut cb: impl FnMut(usize) -> U) {
     let mut idx = 0;
     macro_rules! run_chunked {
         ($size:expr) => {
             if MAX_CHUNK >= $size {
-                while idx + $size < len {
+                while len - idx >= $size {
                     seq!(extra in 0..$size {
                         cb(idx + extra);
                     });
@@ -24,25 +27,293 @@ pub fn for_each_idx_chunked<const MAX_CHUNK: usize, U>(len: usize, mut cb: impl
     run_chunked!(1);
 }
 
-pub fn for_each_aligned_value<const MAX_CHUNK: usize, T, U>(
-    data: &mut [u8],
-    mut cb: impl FnMut(&mut MaybeUninit<T>) -> U,
-) {
+/// Calls `cb` with a pointer to every aligned value of `T` in the `len` bytes at `data`, and
+/// `edge` with the unaligned bytes before the first and after the last of them.
+///
+/// Returns the number of bytes handed to either, which is always `len`.
+///
+/// # Safety
+/// `data` must be valid for whatever `cb` and `edge` do with `len` bytes
+pub unsafe fn for_each_aligned_value<const MAX_CHUNK: usize, T, U>(
+    data: *mut u8,
+    len: usize,
+    mut cb: impl FnMut(*mut T) -> U,
+    mut edge: impl FnMut(*mut u8, usize),
+) -> usize {
     const { assert!(core::mem::size_of::<T>() != 0) };
     unsafe {
-        let (_, data, _) = data.align_to_mut::<MaybeUninit<T>>();
-        for_each_idx_chunked::<MAX_CHUNK, U>(data.len(), |idx| {
-            black_box(cb(black_box(data.get_unchecked_mut(idx))))
+        let head = data.align_offset(align_of::<T>()).min(len);
+        edge(data, head);
+        let values = (len - head) / size_of::<T>();
+        let start = data.add(head).cast::<T>();
+        for_each_idx_chunked::<MAX_CHUNK, U>(values, |idx| {
+            black_box(cb(black_box(start.add(idx))))
         });
+        let done = head + values * size_of::<T>();
+        edge(data.add(done), len - done);
     }
+    len
 }
 
-pub fn read_by<const MAX_CHUNK: usize, T>(data: &mut [u8]) {
-    for_each_aligned_value::<MAX_CHUNK, T, T>(data, |v| unsafe { black_box(v.assume_init_read()) });
+/// Calls `cb` with a pointer to every whole value of `T` in the `len` bytes at `data` counting
+/// from its start, however `data` is aligned, and `edge` with the bytes after the last of them.
+///
+/// Returns the number of bytes handed to either, which is always `len`.
+///
+/// # Safety
+/// See [`for_each_aligned_value`]
+pub unsafe fn for_each_unaligned_value<const MAX_CHUNK: usize, T, U>(
+    data: *mut u8,
+    len: usize,
+    mut cb: impl FnMut(*mut T) -> U,
+    mut edge: impl FnMut(*mut u8, usize),
+) -> usize {
+    const { assert!(core::mem::size_of::<T>() != 0) };
+    let values = len / size_of::<T>();
+    let start = data.cast::<T>();
+    for_each_idx_chunked::<MAX_CHUNK, U>(values, |idx| {
+        black_box(cb(black_box(unsafe { start.add(idx) })))
+    });
+    let done = values * size_of::<T>();
+    edge(unsafe { data.add(done) }, len - done);
+    len
 }
 
-pub unsafe fn write_by<const MAX_CHUNK: usize, T: Copy>(data: &mut [u8], value: T) {
-    for_each_aligned_value::<MAX_CHUNK, T, ()>(data, |v| {
-        v.write(value);
-    });
+/// Where the reads of a strategy put every value they load
+pub trait ReadSink {
+    /// Takes `value`, just loaded from `address`
+    fn consume<T>(address: *const T, value: T);
+}
+
+/// Hides every value from the optimizer and drops it, what the benchmarks read with
+#[derive(Debug, Clone, Copy)]
+pub struct BlackBoxSink;
+
+impl ReadSink for BlackBoxSink {
+    #[inline(always)]
+    fn consume<T>(_address: *const T, value: T) {
+        black_box(value);
+    }
+}
+
+/// Reads every one of the `len` bytes at `data` on its own, for the edges of wider reads
+///
+/// # Safety
+/// `data` must be valid for reads of `len` bytes
+pub unsafe fn read_bytes<S: ReadSink>(data: *const u8, len: usize) {
+    for idx in 0..len {
+        let b = unsafe { data.add(idx) };
+        S::consume(b, unsafe { b.read() });
+    }
+}
+
+/// Fills the `len` bytes at `data` with [`WRITE_PATTERN`] a byte at a time, for the edges of
+/// wider writes
+///
+/// # Safety
+/// `data` must be valid for writes of `len` bytes
+pub unsafe fn write_bytes(data: *mut u8, len: usize) {
+    for idx in 0..len {
+        unsafe { data.add(idx).write(black_box(WRITE_PATTERN)) };
+    }
+}
+
+/// # Safety
+/// `data` must be valid for reads of `len` bytes
+pub unsafe fn read_by<const MAX_CHUNK: usize, T, S: ReadSink>(
+    data: *const u8,
+    len: usize,
+) -> usize {
+    unsafe {
+        for_each_aligned_value::<MAX_CHUNK, T, ()>(
+            data.cast_mut(),
+            len,
+            |v| S::consume(v, v.read()),
+            |data, len| read_bytes::<S>(data, len),
+        )
+    }
+}
+
+/// # Safety
+/// `data` must be valid for reads of `len` bytes
+pub unsafe fn read_unaligned_by<const MAX_CHUNK: usize, T, S: ReadSink>(
+    data: *const u8,
+    len: usize,
+) -> usize {
+    unsafe {
+        for_each_unaligned_value::<MAX_CHUNK, T, ()>(
+            data.cast_mut(),
+            len,
+            |v| S::consume(v, v.read_unaligned()),
+            |data, len| read_bytes::<S>(data, len),
+        )
+    }
+}
+
+/// # Safety
+/// `data` must be valid for writes of `len` bytes, and every byte of `value` must be
+/// [`WRITE_PATTERN`]
+pub unsafe fn write_unaligned_by<const MAX_CHUNK: usize, T: Copy>(
+    data: *mut u8,
+    len: usize,
+    value: T,
+) -> usize {
+    unsafe {
+        for_each_unaligned_value::<MAX_CHUNK, T, ()>(
+            data,
+            len,
+            |v| v.write_unaligned(value),
+            |data, len| write_bytes(data, len),
+        )
+    }
+}
+
+/// # Safety
+/// `data` must be valid for writes of `len` bytes, and every byte of `value` must be
+/// [`WRITE_PATTERN`]
+pub unsafe fn write_by<const MAX_CHUNK: usize, T: Copy>(
+    data: *mut u8,
+    len: usize,
+    value: T,
+) -> usize {
+    unsafe {
+        for_each_aligned_value::<MAX_CHUNK, T, ()>(
+            data,
+            len,
+            |v| v.write(value),
+            |data, len| write_bytes(data, len),
+        )
+    }
+}
+
+/// Copies `len` bytes from `from` to `to` a value of `T` at a time. `copy_value` copies a single
+/// value from a source that may be unaligned to an aligned destination, the bytes before the first
+/// and after the last aligned destination value are copied one at a time.
+///
+/// Returns the number of bytes copied, which is always `len`.
+///
+/// # Safety
+/// `from` must be valid for reads and `to` for writes of `len` bytes, and the two must not overlap
+pub unsafe fn copy_by<const MAX_CHUNK: usize, T>(
+    from: *const u8,
+    to: *mut u8,
+    len: usize,
+    mut copy_value: impl FnMut(*const T, *mut T),
+) -> usize {
+    unsafe {
+        let head = to.align_offset(align_of::<T>()).min(len);
+        copy_bytes(from, to, head);
+        let values = (len - head) / size_of::<T>();
+        let (values_from, values_to) = (from.add(head).cast::<T>(), to.add(head).cast::<T>());
+        for_each_idx_chunked::<MAX_CHUNK, ()>(values, |idx| {
+            copy_value(values_from.add(idx), values_to.add(idx));
+        });
+        let copied = head + values * size_of::<T>();
+        copy_bytes(from.add(copied), to.add(copied), len - copied);
+    }
+    len
+}
+
+/// Copies `len` bytes from `from` to `to` a value of `T` at a time counting from their starts,
+/// however either is aligned. `copy_value` copies a single value between pointers that may both
+/// be unaligned, the bytes after the last whole value are copied one at a time.
+///
+/// Returns the number of bytes copied, which is always `len`.
+///
+/// # Safety
+/// See [`copy_by`]
+pub unsafe fn copy_unaligned_by<const MAX_CHUNK: usize, T>(
+    from: *const u8,
+    to: *mut u8,
+    len: usize,
+    mut copy_value: impl FnMut(*const T, *mut T),
+) -> usize {
+    unsafe {
+        let values = len / size_of::<T>();
+        let (values_from, values_to) = (from.cast::<T>(), to.cast::<T>());
+        for_each_idx_chunked::<MAX_CHUNK, ()>(values, |idx| {
+            copy_value(values_from.add(idx), values_to.add(idx));
+        });
+        let copied = values * size_of::<T>();
+        copy_bytes(from.add(copied), to.add(copied), len - copied);
+    }
+    len
+}
+
+/// Copies `len` bytes one at a time, for the edges of wider copies
+///
+/// # Safety
+/// See [`copy_by`]
+unsafe fn copy_bytes(from: *const u8, to: *mut u8, len: usize) {
+    for idx in 0..len {
+        unsafe {
+            to.add(idx).write(black_box(from.add(idx).read()));
+        }
+    }
+}
+
+/// A single cache line, the unit the prefetching strategies work in
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[derive(Clone, Copy)]
+#[repr(C, align(64))]
+pub struct CacheLine([u8; 64]);
+
+/// Reads every one of the `len` bytes at `data` with 128-bit SSE loads, prefetching with `HINT`
+/// `distance` bytes ahead of every cache line before reading it
+///
+/// # Safety
+/// `data` must be valid for reads of `len` bytes
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub unsafe fn prefetching_read<const HINT: i32, S: ReadSink>(
+    data: *const u8,
+    len: usize,
+    distance: usize,
+) -> usize {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64 as x86;
+    unsafe {
+        for_each_aligned_value::<8, CacheLine, ()>(
+            data.cast_mut(),
+            len,
+            |line| {
+                // Prefetches never fault, so running past the end of the buffer is harmless
+                x86::_mm_prefetch::<HINT>(line.cast::<i8>().wrapping_add(distance));
+                let values = line.cast::<x86::__m128i>();
+                for idx in 0..size_of::<CacheLine>() / size_of::<x86::__m128i>() {
+                    let value = values.add(idx);
+                    S::consume(value, x86::_mm_load_si128(value));
+                }
+            },
+            |data, len| read_bytes::<S>(data, len),
+        )
+    }
+}
+
+/// Copies `len` bytes with 128-bit SSE loads and stores like [`copy_by`], prefetching the source
+/// with `HINT` `distance` bytes ahead of every cache line before copying it
+///
+/// # Safety
+/// See [`copy_by`]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub unsafe fn prefetching_copy<const HINT: i32>(
+    from: *const u8,
+    to: *mut u8,
+    len: usize,
+    distance: usize,
+) -> usize {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64 as x86;
+    unsafe {
+        copy_by::<8, CacheLine>(from, to, len, |from, to| {
+            x86::_mm_prefetch::<HINT>(from.cast::<i8>().wrapping_add(distance));
+            let (from, to) = (from.cast::<x86::__m128i>(), to.cast::<x86::__m128i>());
+            for idx in 0..size_of::<CacheLine>() / size_of::<x86::__m128i>() {
+                x86::_mm_store_si128(to.add(idx), x86::_mm_loadu_si128(from.add(idx)));
+            }
+        })
+    }
 }
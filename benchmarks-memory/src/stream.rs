@@ -0,0 +1,298 @@
+//! The Scale, Add and Triad kernels of the STREAM benchmark, working on three separate `f64` arrays
+//! `a`, `b` and `c` of equal length.
+
+/// A STREAM kernel writing `a` from `b`, `c` and the scalar `q`.
+///
+/// # Safety
+/// The strategy the kernel belongs to must be supported by the CPU
+pub type StreamKernelFn = unsafe fn(a: &mut [f64], b: &[f64], c: &[f64], q: f64);
+
+/// The scalar STREAM uses
+pub const STREAM_SCALAR: f64 = 3.0;
+
+/// The value STREAM kernel buffers get initialized with, keeping every result finite and normal
+pub(crate) const STREAM_INITIAL_VALUE: f64 = 1.0;
+
+/// The length of each of the three arrays [`stream_arrays`] splits `len` bytes into, a whole
+/// number of cache lines
+pub(crate) fn stream_array_len(len: usize) -> usize {
+    const ELEMENTS_PER_LINE: usize = crate::CACHE_LINE / size_of::<f64>();
+    len / size_of::<f64>() / 3 / ELEMENTS_PER_LINE * ELEMENTS_PER_LINE
+}
+
+/// Splits `memory` into the `a`, `b` and `c` arrays, each starting on a cache line boundary if
+/// `memory` does
+pub(crate) fn stream_arrays(memory: &mut [u8]) -> (&mut [f64], &mut [f64], &mut [f64]) {
+    // SAFETY: Every bit pattern is a valid f64
+    let (_, words, _) = unsafe { memory.align_to_mut::<f64>() };
+    let len = stream_array_len(size_of_val(words));
+    let (a, rest) = words.split_at_mut(len);
+    let (b, rest) = rest.split_at_mut(len);
+    (a, b, &mut rest[..len])
+}
+
+/// Plain loops, which the compiler is free to vectorize with the baseline instruction set
+pub(crate) mod scalar {
+    pub(crate) fn scale(a: &mut [f64], b: &[f64], _c: &[f64], q: f64) {
+        for (a, b) in a.iter_mut().zip(b) {
+            *a = q * b;
+        }
+    }
+    pub(crate) fn add(a: &mut [f64], b: &[f64], c: &[f64], _q: f64) {
+        for ((a, b), c) in a.iter_mut().zip(b).zip(c) {
+            *a = b + c;
+        }
+    }
+    pub(crate) fn triad(a: &mut [f64], b: &[f64], c: &[f64], q: f64) {
+        for ((a, b), c) in a.iter_mut().zip(b).zip(c) {
+            *a = b + q * c;
+        }
+    }
+}
+
+/// Generates the Scale, Add and Triad kernels for a single vector width.
+/// Elements that don't fill a whole vector are left to the scalar kernels.
+///
+/// With `NON_TEMPORAL` the kernels write `a` with streaming stores if it is aligned to the vector
+/// width, followed by a fence so that the stores are ordered before anything after the kernel.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+macro_rules! simd_kernels {
+    (
+        $name:ident, $feature:literal, $lanes:literal, $vector:ident,
+        $set1:ident, $load:ident, $store:ident, $stream:ident, $add:ident, $mul:ident
+    ) => {
+        pub(crate) mod $name {
+            #[cfg(target_arch = "x86")]
+            use core::arch::x86;
+            #[cfg(target_arch = "x86_64")]
+            use core::arch::x86_64 as x86;
+
+            /// Stores `value` at `a`, streaming it past the caches if `stream`.
+            ///
+            /// # Safety
+            /// `a` must be valid for a whole vector, and aligned to it if `stream`
+            #[target_feature(enable = $feature)]
+            #[inline]
+            unsafe fn store(a: *mut f64, value: x86::$vector, stream: bool) {
+                unsafe {
+                    if stream {
+                        x86::$stream(a, value);
+                    } else {
+                        x86::$store(a, value);
+                    }
+                }
+            }
+
+            /// Whether streaming stores can be used for `a`
+            fn can_stream<const NON_TEMPORAL: bool>(a: &[f64]) -> bool {
+                NON_TEMPORAL && a.as_ptr().addr().is_multiple_of($lanes * size_of::<f64>())
+            }
+
+            #[target_feature(enable = $feature)]
+            pub(crate) unsafe fn scale<const NON_TEMPORAL: bool>(
+                a: &mut [f64],
+                b: &[f64],
+                _c: &[f64],
+                q: f64,
+            ) {
+                let len = a.len().min(b.len()) / $lanes * $lanes;
+                let stream = can_stream::<NON_TEMPORAL>(a);
+                let q_vec = x86::$set1(q);
+                for idx in (0..len).step_by($lanes) {
+                    unsafe {
+                        let b_vec = x86::$load(b.as_ptr().add(idx));
+                        store(a.as_mut_ptr().add(idx), x86::$mul(q_vec, b_vec), stream);
+                    }
+                }
+                if stream {
+                    x86::_mm_sfence();
+                }
+                super::scalar::scale(&mut a[len..], &b[len..], &[], q);
+            }
+            #[target_feature(enable = $feature)]
+            pub(crate) unsafe fn add<const NON_TEMPORAL: bool>(
+                a: &mut [f64],
+                b: &[f64],
+                c: &[f64],
+                q: f64,
+            ) {
+                let len = a.len().min(b.len()).min(c.len()) / $lanes * $lanes;
+                let stream = can_stream::<NON_TEMPORAL>(a);
+                for idx in (0..len).step_by($lanes) {
+                    unsafe {
+                        let b_vec = x86::$load(b.as_ptr().add(idx));
+                        let c_vec = x86::$load(c.as_ptr().add(idx));
+                        store(a.as_mut_ptr().add(idx), x86::$add(b_vec, c_vec), stream);
+                    }
+                }
+                if stream {
+                    x86::_mm_sfence();
+                }
+                super::scalar::add(&mut a[len..], &b[len..], &c[len..], q);
+            }
+            #[target_feature(enable = $feature)]
+            pub(crate) unsafe fn triad<const NON_TEMPORAL: bool>(
+                a: &mut [f64],
+                b: &[f64],
+                c: &[f64],
+                q: f64,
+            ) {
+                let len = a.len().min(b.len()).min(c.len()) / $lanes * $lanes;
+                let stream = can_stream::<NON_TEMPORAL>(a);
+                let q_vec = x86::$set1(q);
+                for idx in (0..len).step_by($lanes) {
+                    unsafe {
+                        let b_vec = x86::$load(b.as_ptr().add(idx));
+                        let c_vec = x86::$load(c.as_ptr().add(idx));
+                        let result = x86::$add(b_vec, x86::$mul(q_vec, c_vec));
+                        store(a.as_mut_ptr().add(idx), result, stream);
+                    }
+                }
+                if stream {
+                    x86::_mm_sfence();
+                }
+                super::scalar::triad(&mut a[len..], &b[len..], &c[len..], q);
+            }
+        }
+    };
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+simd_kernels!(
+    sse,
+    "sse2",
+    2,
+    __m128d,
+    _mm_set1_pd,
+    _mm_loadu_pd,
+    _mm_storeu_pd,
+    _mm_stream_pd,
+    _mm_add_pd,
+    _mm_mul_pd
+);
+#[cfg(target_arch = "x86_64")]
+simd_kernels!(
+    avx2,
+    "avx",
+    4,
+    __m256d,
+    _mm256_set1_pd,
+    _mm256_loadu_pd,
+    _mm256_storeu_pd,
+    _mm256_stream_pd,
+    _mm256_add_pd,
+    _mm256_mul_pd
+);
+#[cfg(target_arch = "x86_64")]
+simd_kernels!(
+    avx512,
+    "avx512f",
+    8,
+    __m512d,
+    _mm512_set1_pd,
+    _mm512_loadu_pd,
+    _mm512_storeu_pd,
+    _mm512_stream_pd,
+    _mm512_add_pd,
+    _mm512_mul_pd
+);
+
+/// Kernels working on pairs of 128-bit vectors, written with a single store pair instruction.
+/// With `NON_TEMPORAL` that is `stnp`, hinting that the data won't be read again soon.
+#[cfg(target_arch = "aarch64")]
+pub(crate) mod neon {
+    use core::arch::{aarch64 as arm, asm};
+
+    /// Elements per pair of vectors
+    const LANES: usize = 4;
+
+    /// Stores `low` and `high` to `a`
+    ///
+    /// # Safety
+    /// `a` must be valid for both vectors
+    #[target_feature(enable = "neon")]
+    #[inline]
+    unsafe fn store_pair<const NON_TEMPORAL: bool>(
+        a: *mut f64,
+        low: arm::float64x2_t,
+        high: arm::float64x2_t,
+    ) {
+        unsafe {
+            if NON_TEMPORAL {
+                asm!(
+                    "stnp {low:q}, {high:q}, [{a}]",
+                    a = in(reg) a,
+                    low = in(vreg) low,
+                    high = in(vreg) high,
+                    options(nostack, preserves_flags),
+                );
+            } else {
+                arm::vst1q_f64(a, low);
+                arm::vst1q_f64(a.add(2), high);
+            }
+        }
+    }
+
+    #[target_feature(enable = "neon")]
+    pub(crate) unsafe fn scale<const NON_TEMPORAL: bool>(
+        a: &mut [f64],
+        b: &[f64],
+        _c: &[f64],
+        q: f64,
+    ) {
+        let len = a.len().min(b.len()) / LANES * LANES;
+        let q_vec = arm::vdupq_n_f64(q);
+        for idx in (0..len).step_by(LANES) {
+            unsafe {
+                let b = b.as_ptr().add(idx);
+                let low = arm::vmulq_f64(q_vec, arm::vld1q_f64(b));
+                let high = arm::vmulq_f64(q_vec, arm::vld1q_f64(b.add(2)));
+                store_pair::<NON_TEMPORAL>(a.as_mut_ptr().add(idx), low, high);
+            }
+        }
+        super::scalar::scale(&mut a[len..], &b[len..], &[], q);
+    }
+    #[target_feature(enable = "neon")]
+    pub(crate) unsafe fn add<const NON_TEMPORAL: bool>(
+        a: &mut [f64],
+        b: &[f64],
+        c: &[f64],
+        q: f64,
+    ) {
+        let len = a.len().min(b.len()).min(c.len()) / LANES * LANES;
+        for idx in (0..len).step_by(LANES) {
+            unsafe {
+                let b = b.as_ptr().add(idx);
+                let c = c.as_ptr().add(idx);
+                let low = arm::vaddq_f64(arm::vld1q_f64(b), arm::vld1q_f64(c));
+                let high = arm::vaddq_f64(arm::vld1q_f64(b.add(2)), arm::vld1q_f64(c.add(2)));
+                store_pair::<NON_TEMPORAL>(a.as_mut_ptr().add(idx), low, high);
+            }
+        }
+        super::scalar::add(&mut a[len..], &b[len..], &c[len..], q);
+    }
+    #[target_feature(enable = "neon")]
+    pub(crate) unsafe fn triad<const NON_TEMPORAL: bool>(
+        a: &mut [f64],
+        b: &[f64],
+        c: &[f64],
+        q: f64,
+    ) {
+        let len = a.len().min(b.len()).min(c.len()) / LANES * LANES;
+        let q_vec = arm::vdupq_n_f64(q);
+        for idx in (0..len).step_by(LANES) {
+            unsafe {
+                let b = b.as_ptr().add(idx);
+                let c = c.as_ptr().add(idx);
+                let low =
+                    arm::vaddq_f64(arm::vld1q_f64(b), arm::vmulq_f64(q_vec, arm::vld1q_f64(c)));
+                let high = arm::vaddq_f64(
+                    arm::vld1q_f64(b.add(2)),
+                    arm::vmulq_f64(q_vec, arm::vld1q_f64(c.add(2))),
+                );
+                store_pair::<NON_TEMPORAL>(a.as_mut_ptr().add(idx), low, high);
+            }
+        }
+        super::scalar::triad(&mut a[len..], &b[len..], &c[len..], q);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{
+    Config, Destination, LineOrder, MemoryOperation, State, TestResult, allocate_thread_buffer,
+    execute_pass, initialize_thread_buffer, misaligned, pin_current_thread,
+};
+use benchmarks_core::ProgressTracker;
+use std::{sync::Arc, time::Instant};
+
+/// Every sweep step processes at least this many bytes per thread, so that the steps that fit
+/// into the L1 cache still run long enough to be measured reliably.
+const MIN_BYTES_PER_STEP: usize = 256 * 1024 * 1024;
+
+/// All sizes of a sweep are rounded down to a multiple of this.
+const SIZE_GRANULARITY: usize = 64;
+
+/// A geometric series of working set sizes, e.g. 4 KiB, 8 KiB, 16 KiB, ... 1 GiB
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepRange {
+    pub min_size: usize,
+    pub max_size: usize,
+    /// How many sizes are measured each time the working set doubles
+    pub steps_per_doubling: usize,
+}
+
+impl Default for SweepRange {
+    fn default() -> Self {
+        Self {
+            min_size: 4 * 1024,
+            max_size: 1024 * 1024 * 1024,
+            steps_per_doubling: 1,
+        }
+    }
+}
+
+impl SweepRange {
+    /// All sizes of the series, in ascending order. Always contains at least `min_size`.
+    #[must_use]
+    pub fn sizes(&self) -> Vec<usize> {
+        let min_size = self.min_size.max(SIZE_GRANULARITY);
+        let steps_per_doubling = self.steps_per_doubling.max(1);
+        let mut sizes = vec![min_size / SIZE_GRANULARITY * SIZE_GRANULARITY];
+        for step in 1.. {
+            let size = min_size as f64 * (step as f64 / steps_per_doubling as f64).exp2();
+            let size = size as usize / SIZE_GRANULARITY * SIZE_GRANULARITY;
+            if size > self.max_size {
+                break;
+            }
+            if sizes.last() != Some(&size) {
+                sizes.push(size);
+            }
+        }
+        sizes
+    }
+}
+
+/// The throughput of all threads at a single working set size
+#[derive(Debug, Clone)]
+pub struct SweepPoint {
+    /// The combined size of all threads' working sets
+    pub memory_size: usize,
+    pub result: TestResult,
+}
+
+#[derive(Debug)]
+pub struct MemorySweepBench {
+    config: Config,
+    sizes: Arc<[usize]>,
+    threads: Vec<std::thread::JoinHandle<Option<Vec<TestResult>>>>,
+    progress: Arc<ProgressTracker<State>>,
+}
+
+impl Config {
+    /// Starts a sweep running `operation` with `strategy` over every size of `range`.
+    /// `memory_size`, `warmup_passes` and `run_duration` are ignored, every thread allocates its
+    /// share of the largest size once and works on a growing prefix of it.
+    /// Copies always go to a buffer of the same thread regardless of `copy_placement`.
+    #[must_use]
+    pub fn start_sweep(mut self, range: &SweepRange) -> MemorySweepBench {
+        let sizes: Arc<[usize]> = range.sizes().into();
+        self.memory_size = sizes.last().copied().unwrap_or(range.min_size);
+        let progress = Arc::new(ProgressTracker::new(
+            self.threads as u64,
+            self.threads,
+            State::Allocating,
+        ));
+        let cpus = self.pinning_order();
+        let mut bench = MemorySweepBench {
+            config: self,
+            sizes,
+            threads: Vec::new(),
+            progress,
+        };
+        for worker in 0..bench.config.threads {
+            bench.spawn_worker(cpus.get(worker % cpus.len().max(1)).copied());
+        }
+        bench
+    }
+}
+
+impl MemorySweepBench {
+    #[must_use]
+    pub fn progress(&self) -> Arc<ProgressTracker<State>> {
+        Arc::clone(&self.progress)
+    }
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        (self.progress.stop_requested() || self.progress.load_state() == State::Done) && {
+            self.threads.iter().all(|t| t.is_finished())
+        }
+    }
+    /// Combines the results of all threads into a throughput-vs-size series.
+    /// The runtime of every point is that of the slowest thread.
+    #[must_use]
+    pub fn wait_for_results(self) -> Vec<SweepPoint> {
+        let Self { threads, sizes, .. } = self;
+        let mut points: Vec<SweepPoint> = sizes
+            .iter()
+            .map(|&memory_size| SweepPoint {
+                memory_size,
+                result: TestResult::default(),
+            })
+            .collect();
+        let mut any_finished = false;
+        for thread in threads {
+            let Some(samples) = thread.join().unwrap() else {
+                continue;
+            };
+            for (point, sample) in points.iter_mut().zip(samples) {
+                if any_finished {
+                    point.result.combine_parallel(&sample);
+                } else {
+                    point.result = sample;
+                }
+            }
+            any_finished = true;
+        }
+        if !any_finished {
+            points.clear();
+        }
+        points
+    }
+    fn spawn_worker(&mut self, cpu: Option<usize>) {
+        let config = self.config.clone();
+        let sizes = Arc::clone(&self.sizes);
+        let progress = Arc::clone(&self.progress);
+        self.threads.push(std::thread::spawn(move || {
+            Self::run(&config, &sizes, cpu, &progress)
+        }));
+    }
+    fn run(
+        config: &Config,
+        sizes: &[usize],
+        cpu: Option<usize>,
+        progress: &Arc<ProgressTracker<State>>,
+    ) -> Option<Vec<TestResult>> {
+        let cpu = cpu.filter(|&cpu| pin_current_thread(cpu));
+        let (memory, memory_node, backing) = allocate_thread_buffer(config, progress);
+        let destination = (config.operation == MemoryOperation::Copy)
+            .then(|| Destination::allocate(config, memory.len(), memory_node));
+        let mut memory = initialize_thread_buffer(config, progress, memory)?;
+        let mut results = Vec::with_capacity(sizes.len());
+        for (step, &size) in sizes.iter().enumerate() {
+            let thread_size = (size / config.threads)
+                .max(SIZE_GRANULARITY)
+                .min(memory.len());
+            let memory = misaligned(config, &raw mut memory[..thread_size]);
+            let chunk_size = config.block_size.clamp(1, thread_size);
+            let repeats = config.passes.max(MIN_BYTES_PER_STEP.div_ceil(thread_size));
+            let order = LineOrder::new(config, memory.len());
+            progress.transition_state(
+                State::Sweeping(step + 1, sizes.len()),
+                (repeats * config.threads) as u64,
+            );
+            if progress.stop_requested() {
+                return None;
+            }
+
+            let mut memory_processed = 0;
+            let mut bytes_written = 0;
+            let start = Instant::now();
+            for _ in 0..repeats {
+                let destination = destination.as_ref().map(|d| misaligned(config, d.as_ptr()));
+                // SAFETY: The buffer and the destination are private to this worker
+                unsafe {
+                    execute_pass(
+                        config,
+                        memory,
+                        destination,
+                        &order,
+                        chunk_size,
+                        &mut bytes_written,
+                        |processed| {
+                            memory_processed += processed;
+                            true
+                        },
+                    )
+                }?;
+                progress.add(1);
+                if progress.stop_requested() {
+                    return None;
+                }
+            }
+            let end = Instant::now();
+            results.push(TestResult {
+                memory_processed,
+                memory_traffic: memory_processed * config.operation.traffic_multiplier(),
+                bytes_written,
+                runtime: end - start,
+                measure_start: Some(start),
+                measure_end: Some(end),
+                // The repeats of the smallest sizes are too short to be timed individually
+                pass_runtimes: Vec::new(),
+                cpu,
+                memory_node,
+                destination_node: destination.as_ref().and_then(|d| d.node),
+                backing,
+            });
+        }
+        progress.transition_state(State::Done, config.threads as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+        progress.add(1);
+        Some(results)
+    }
+}
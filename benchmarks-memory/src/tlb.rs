@@ -0,0 +1,277 @@
+//! TLB reach: the latency of touching a single cache line in each of a growing number of pages,
+//! which jumps every time the pages stop fitting into another level of TLB.
+
+use crate::{
+    AllocatedBacking, BufferBacking, CHAIN_STRIDE, Config, LOADS_PER_PROGRESS_UPDATE, State,
+    allocate_bound_buffer, chase, link_pointer_chain, pin_current_thread,
+};
+use benchmarks_core::ProgressTracker;
+use std::{hint::black_box, sync::Arc, time::Duration};
+
+/// The most pages a series of a TLB reach benchmark goes up to, far past any TLB, as their cache
+/// lines start missing the caches as well after a few thousand pages
+pub const MAX_TLB_REACH_PAGES: usize = 1 << 16;
+
+/// The page counts measured per doubling of the number of pages
+const STEPS_PER_OCTAVE: u32 = 4;
+
+/// The loads every timed pass performs, at least one for every page
+const LOADS_PER_PASS: usize = 1 << 18;
+
+/// How much slower the page counts after a knee have to be than those before it
+const KNEE_THRESHOLD: f64 = 1.3;
+
+/// The page counts on either side of a knee compared, so that a single noisy one isn't a knee
+const KNEE_WINDOW: usize = 2;
+
+/// The latency of accessing a single cache line in each of `pages` pages
+#[derive(Debug, Clone)]
+pub struct TlbReachPoint {
+    pub pages: usize,
+    /// The number of dependent loads performed
+    pub loads: usize,
+    pub runtime: Duration,
+}
+
+impl TlbReachPoint {
+    /// The average time a single access took, in nanoseconds
+    #[must_use]
+    pub fn ns_per_access(&self) -> f64 {
+        self.runtime.as_secs_f64() * 1e9 / self.loads as f64
+    }
+}
+
+/// The points of a single page size, with growing page counts
+#[derive(Debug, Clone)]
+pub struct TlbReachSeries {
+    /// The distance between the cache lines of two pages, the size of the pages requested
+    pub page_size: usize,
+    pub backing: AllocatedBacking,
+    pub points: Vec<TlbReachPoint>,
+}
+
+impl TlbReachSeries {
+    /// The page counts right before the latency jumps, where the pages stop fitting into a level
+    /// of TLB, or past the largest one, into the caches.
+    ///
+    /// A knee is a page count that the next two are at least 30% slower than, compared to the
+    /// slower of it and the one before. Of consecutive knees, only the first one is kept.
+    #[must_use]
+    pub fn knees(&self) -> Vec<usize> {
+        let ns: Vec<f64> = self
+            .points
+            .iter()
+            .map(TlbReachPoint::ns_per_access)
+            .collect();
+        let is_knee = |idx: usize| {
+            let before = ns[(idx + 1).saturating_sub(KNEE_WINDOW)..=idx]
+                .iter()
+                .copied()
+                .fold(f64::NEG_INFINITY, f64::max);
+            let after = ns[idx + 1..(idx + 1 + KNEE_WINDOW).min(ns.len())]
+                .iter()
+                .copied()
+                .fold(f64::INFINITY, f64::min);
+            after >= before * KNEE_THRESHOLD
+        };
+        (0..ns.len().saturating_sub(1))
+            .filter(|&idx| is_knee(idx) && (idx == 0 || !is_knee(idx - 1)))
+            .map(|idx| self.points[idx].pages)
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct TlbReachBench {
+    thread: std::thread::JoinHandle<Option<Vec<TlbReachSeries>>>,
+    progress: Arc<ProgressTracker<State>>,
+}
+
+impl Config {
+    /// The backings [`Config::start_tlb_reach`] measures: regular pages, then `backing` if it is
+    /// made of huge pages, transparent huge pages otherwise
+    #[must_use]
+    pub fn tlb_reach_backings(&self) -> [BufferBacking; 2] {
+        let huge = if self.backing.page_size() > BufferBacking::Mmap.page_size() {
+            self.backing
+        } else {
+            BufferBacking::TransparentHugePages
+        };
+        [BufferBacking::Mmap, huge]
+    }
+    /// The page counts [`Config::start_tlb_reach`] measures with pages of `page_size` bytes:
+    /// four per doubling, up to as many pages as fit into `memory_size`, but no more than
+    /// [`MAX_TLB_REACH_PAGES`]
+    #[must_use]
+    pub fn tlb_reach_page_counts(&self, page_size: usize) -> Vec<usize> {
+        let max_pages = (self.memory_size / page_size).clamp(1, MAX_TLB_REACH_PAGES);
+        let mut counts: Vec<usize> = (0..)
+            .map(|step| {
+                2_f64
+                    .powf(f64::from(step) / f64::from(STEPS_PER_OCTAVE))
+                    .round() as usize
+            })
+            .take_while(|&pages| pages <= max_pages)
+            .collect();
+        counts.dedup();
+        counts
+    }
+    /// Starts a TLB reach benchmark, chasing pointers through a single cache line in each of a
+    /// growing number of pages, in a random order, for every backing of
+    /// [`Config::tlb_reach_backings`].
+    ///
+    /// The cache lines sit at a different offset in every page, so that they don't all compete
+    /// for the same cache sets. Every page count runs `passes` timed passes after an untimed one
+    /// that faults the pages in and fills the TLBs.
+    ///
+    /// Runs on a single thread, pinned to the first CPU of `pinning` if any. `threads`,
+    /// `operation`, `strategy` and `init_type` are ignored.
+    #[must_use]
+    pub fn start_tlb_reach(self) -> TlbReachBench {
+        let progress = Arc::new(ProgressTracker::new(1, 1, State::Allocating));
+        let thread = {
+            let progress = Arc::clone(&progress);
+            std::thread::spawn(move || Self::run_tlb_reach(&self, &progress))
+        };
+        TlbReachBench { thread, progress }
+    }
+    fn run_tlb_reach(&self, progress: &ProgressTracker<State>) -> Option<Vec<TlbReachSeries>> {
+        if let Some(&cpu) = self.pinning_order().first() {
+            pin_current_thread(cpu);
+        }
+        let backings = self.tlb_reach_backings();
+        let page_counts = backings.map(|backing| self.tlb_reach_page_counts(backing.page_size()));
+        let steps = page_counts.iter().map(Vec::len).sum();
+        let passes = self.passes.max(1);
+        let mut step = 0;
+        let mut series = Vec::with_capacity(backings.len());
+        for (backing, page_counts) in backings.into_iter().zip(page_counts) {
+            let page_size = backing.page_size();
+            let max_pages = page_counts.last().copied().unwrap_or(1);
+            let config = Config {
+                backing,
+                ..self.clone()
+            };
+            let (mut memory, _, allocated) = allocate_bound_buffer(&config, max_pages * page_size);
+            let mut points = Vec::with_capacity(page_counts.len());
+            for pages in page_counts {
+                step += 1;
+                let loads = LOADS_PER_PASS.max(pages);
+                progress.transition_state(
+                    State::TlbReach(step, steps),
+                    (pages + loads * (passes + 1)) as u64,
+                );
+                // Every page holds a single link, a line further into the page than the last one
+                let offset = |page: usize| page * page_size + (page * CHAIN_STRIDE) % page_size;
+                let mut current = link_pointer_chain(&mut memory, pages, offset, progress)?;
+                let mut runtime = Duration::ZERO;
+                for pass in 0..=passes {
+                    let start = std::time::Instant::now();
+                    let mut remaining = loads;
+                    while remaining != 0 {
+                        let chunk = remaining.min(LOADS_PER_PROGRESS_UPDATE);
+                        // SAFETY: The chain was just built in `memory`, which is still alive
+                        current = unsafe { chase(current, chunk) };
+                        remaining -= chunk;
+                        progress.add(chunk as u64);
+                        if progress.stop_requested() {
+                            return None;
+                        }
+                    }
+                    // The first pass only warms up
+                    if pass != 0 {
+                        runtime += start.elapsed();
+                    }
+                }
+                black_box(current);
+                points.push(TlbReachPoint {
+                    pages,
+                    loads: loads * passes,
+                    runtime,
+                });
+            }
+            series.push(TlbReachSeries {
+                page_size,
+                backing: allocated,
+                points,
+            });
+        }
+        progress.transition_state(State::Done, 1);
+        progress.add(1);
+        Some(series)
+    }
+}
+
+impl TlbReachBench {
+    #[must_use]
+    pub fn progress(&self) -> Arc<ProgressTracker<State>> {
+        Arc::clone(&self.progress)
+    }
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        (self.progress.stop_requested() || self.progress.load_state() == State::Done)
+            && self.thread.is_finished()
+    }
+    /// A series for every backing of [`Config::tlb_reach_backings`], empty if the benchmark was
+    /// cancelled
+    #[must_use]
+    pub fn wait_for_results(self) -> Vec<TlbReachSeries> {
+        self.thread.join().unwrap().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A series with a point for every latency in `ns`, at 1, 2, 4, ... pages
+    fn series(ns: &[f64]) -> TlbReachSeries {
+        let loads = 1000;
+        TlbReachSeries {
+            page_size: 4096,
+            backing: AllocatedBacking::default(),
+            points: ns
+                .iter()
+                .enumerate()
+                .map(|(idx, &ns)| TlbReachPoint {
+                    pages: 1 << idx,
+                    loads,
+                    runtime: Duration::from_nanos((ns * loads as f64) as u64),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn flat_series_has_no_knees() {
+        assert_eq!(series(&[5.0; 10]).knees(), []);
+        assert_eq!(series(&[]).knees(), []);
+        assert_eq!(series(&[5.0]).knees(), []);
+    }
+
+    #[test]
+    fn single_step_has_a_knee_at_the_last_fast_count() {
+        let knees = series(&[5.0, 5.0, 5.0, 5.0, 10.0, 10.0, 10.0, 10.0]).knees();
+        assert_eq!(knees, [8]);
+    }
+
+    #[test]
+    fn two_steps_have_two_knees() {
+        let ns = [
+            2.0, 2.0, 2.0, 2.0, 5.0, 5.0, 5.0, 5.0, 15.0, 15.0, 15.0, 15.0,
+        ];
+        assert_eq!(series(&ns).knees(), [8, 128]);
+    }
+
+    #[test]
+    fn single_noisy_point_is_no_knee() {
+        let knees = series(&[5.0, 5.0, 5.0, 20.0, 5.0, 5.0, 5.0]).knees();
+        assert_eq!(knees, []);
+    }
+
+    #[test]
+    fn gradual_step_has_a_single_knee() {
+        let knees = series(&[5.0, 5.0, 7.0, 10.0, 10.0, 10.0]).knees();
+        assert_eq!(knees, [2]);
+    }
+}
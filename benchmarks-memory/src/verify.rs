@@ -0,0 +1,451 @@
+//! Classic memtest patterns run over the per-thread buffers, looking for bad memory instead of
+//! measuring it. Every test works on whole 64-bit words with volatile accesses, so that every
+//! write and read actually reaches memory in the order the test expects.
+
+use crate::{AllocatedBacking, Config, State, allocate_thread_buffer, pin_current_thread};
+use benchmarks_core::{ProgressTracker, SelectableEnum};
+use rand::{Rng, SeedableRng};
+use std::{mem::MaybeUninit, sync::Arc, time::Duration};
+
+/// The number of words processed between two progress updates
+const WORDS_PER_PROGRESS_UPDATE: usize = 64 * 1024;
+
+/// At most this many mismatches are kept per thread, any further ones are only counted
+pub const MAX_RECORDED_MISMATCHES: usize = 1024;
+
+/// The patterns [`VerificationTest::MovingInversions`] runs with, each along with its inversion
+const MOVING_INVERSION_PATTERNS: [u64; 4] = [
+    0x0000_0000_0000_0000,
+    0x5555_5555_5555_5555,
+    0x3333_3333_3333_3333,
+    0x0F0F_0F0F_0F0F_0F0F,
+];
+
+/// A single memory test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationTest {
+    /// Fills the buffer with a single set bit, verifying it once for each of the 64 positions
+    WalkingOnes,
+    /// Fills the buffer with a single cleared bit, verifying it once for each of the 64 positions
+    WalkingZeros,
+    /// Fills the buffer with a pattern, then verifies and inverts every word front to back,
+    /// then verifies and restores every word back to front
+    MovingInversions,
+    /// Writes the address of every word into it and verifies it, then does the same with the
+    /// inverted addresses
+    AddressInAddress,
+    /// Fills the buffer with random words seeded by `Config::pattern_seed`, then verifies and
+    /// inverts them and verifies the inversion
+    RandomPattern,
+}
+
+impl SelectableEnum for VerificationTest {
+    fn all_values() -> &'static [Self] {
+        use VerificationTest::*;
+        &[
+            WalkingOnes,
+            WalkingZeros,
+            MovingInversions,
+            AddressInAddress,
+            RandomPattern,
+        ]
+    }
+    fn as_str(&self) -> &'static str {
+        use VerificationTest::*;
+        match self {
+            WalkingOnes => "Walking ones",
+            WalkingZeros => "Walking zeros",
+            MovingInversions => "Moving inversions",
+            AddressInAddress => "Address in address",
+            RandomPattern => "Random pattern",
+        }
+    }
+}
+
+impl VerificationTest {
+    /// How many times the test goes over every word of the buffer
+    #[must_use]
+    pub const fn word_passes(&self) -> usize {
+        match self {
+            Self::WalkingOnes | Self::WalkingZeros => 2 * u64::BITS as usize,
+            Self::MovingInversions => 3 * MOVING_INVERSION_PATTERNS.len(),
+            Self::AddressInAddress => 4,
+            Self::RandomPattern => 3,
+        }
+    }
+}
+
+/// A word that didn't hold the value a test last wrote to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    pub test: VerificationTest,
+    /// The offset of the word from the start of the thread's buffer, in bytes
+    pub offset: usize,
+    pub expected: u64,
+    pub observed: u64,
+}
+
+impl Mismatch {
+    /// The bits that differ between the expected and the observed value
+    #[must_use]
+    pub const fn flipped_bits(&self) -> u64 {
+        self.expected ^ self.observed
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VerificationResult {
+    /// The size of the buffer every test went over
+    pub bytes_tested: usize,
+    /// The first [`MAX_RECORDED_MISMATCHES`] mismatches, in the order they were found
+    pub mismatches: Vec<Mismatch>,
+    /// The number of mismatches found, including those that weren't recorded
+    pub mismatch_count: usize,
+    pub runtime: Duration,
+    /// The CPU the worker was pinned to, if any
+    pub cpu: Option<usize>,
+    /// The NUMA node the buffer was bound to, if any
+    pub memory_node: Option<usize>,
+    pub backing: AllocatedBacking,
+}
+
+impl VerificationResult {
+    fn record(&mut self, mismatch: Mismatch) {
+        self.mismatch_count += 1;
+        if self.mismatches.len() < MAX_RECORDED_MISMATCHES {
+            self.mismatches.push(mismatch);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MemoryVerificationBench {
+    config: Config,
+    tests: Arc<[VerificationTest]>,
+    threads: Vec<std::thread::JoinHandle<Option<VerificationResult>>>,
+    progress: Arc<ProgressTracker<State>>,
+}
+
+impl Config {
+    /// Starts running every test of `tests` over the buffer of every thread, repeating the whole
+    /// list `passes` times. The random pattern differs between repetitions.
+    /// `operation`, `strategy`, `init_type`, `warmup_passes` and `run_duration` are ignored.
+    #[must_use]
+    pub fn start_verification(self, tests: &[VerificationTest]) -> MemoryVerificationBench {
+        let progress = Arc::new(ProgressTracker::new(
+            self.threads as u64,
+            self.threads,
+            State::Allocating,
+        ));
+        let cpus = self.pinning_order();
+        let mut bench = MemoryVerificationBench {
+            config: self,
+            tests: tests.into(),
+            threads: Vec::new(),
+            progress,
+        };
+        for worker in 0..bench.config.threads {
+            bench.spawn_worker(cpus.get(worker % cpus.len().max(1)).copied());
+        }
+        bench
+    }
+}
+
+impl MemoryVerificationBench {
+    #[must_use]
+    pub fn progress(&self) -> Arc<ProgressTracker<State>> {
+        Arc::clone(&self.progress)
+    }
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        (self.progress.stop_requested() || self.progress.load_state() == State::Done) && {
+            self.threads.iter().all(|t| t.is_finished())
+        }
+    }
+    /// The results of every thread, in order
+    #[must_use]
+    pub fn wait_for_results(self) -> Vec<VerificationResult> {
+        let Self { threads, .. } = self;
+        let mut results = Vec::new();
+        for thread in threads {
+            let Some(sample) = thread.join().unwrap() else {
+                continue;
+            };
+            results.push(sample);
+        }
+        results
+    }
+    fn spawn_worker(&mut self, cpu: Option<usize>) {
+        let config = self.config.clone();
+        let tests = Arc::clone(&self.tests);
+        let progress = Arc::clone(&self.progress);
+        self.threads.push(std::thread::spawn(move || {
+            Self::run(&config, &tests, cpu, &progress)
+        }));
+    }
+    fn run(
+        config: &Config,
+        tests: &[VerificationTest],
+        cpu: Option<usize>,
+        progress: &Arc<ProgressTracker<State>>,
+    ) -> Option<VerificationResult> {
+        let cpu = cpu.filter(|&cpu| pin_current_thread(cpu));
+        let (mut memory, memory_node, backing) = allocate_thread_buffer(config, progress);
+        // SAFETY: Every test writes a word before it reads it, and any bit pattern is a valid u64
+        let (_, words, _) = unsafe { memory.align_to_mut::<MaybeUninit<u64>>() };
+        let mut result = VerificationResult {
+            bytes_tested: size_of_val(words),
+            cpu,
+            memory_node,
+            backing,
+            ..VerificationResult::default()
+        };
+        let mut words = Words {
+            words,
+            progress,
+            result: &mut result,
+        };
+        let steps = tests.len() * config.passes;
+        let start = std::time::Instant::now();
+        for round in 0..config.passes {
+            for (idx, &test) in tests.iter().enumerate() {
+                progress.transition_state(
+                    State::Verifying(round * tests.len() + idx + 1, steps),
+                    (test.word_passes() * words.words.len() * config.threads) as u64,
+                );
+                if progress.stop_requested() {
+                    return None;
+                }
+                let seed = config.pattern_seed.wrapping_add(round as u64);
+                words.run_test(test, seed)?;
+            }
+        }
+        result.runtime = start.elapsed();
+        progress.transition_state(State::Done, config.threads as u64);
+        if progress.stop_requested() {
+            return None;
+        }
+        progress.add(1);
+        Some(result)
+    }
+}
+
+/// The words of a thread's buffer along with where the tests report to
+struct Words<'a> {
+    words: &'a mut [MaybeUninit<u64>],
+    progress: &'a ProgressTracker<State>,
+    result: &'a mut VerificationResult,
+}
+
+impl Words<'_> {
+    /// Runs a single test over every word, returning `None` as soon as a stop was requested
+    fn run_test(&mut self, test: VerificationTest, seed: u64) -> Option<()> {
+        match test {
+            VerificationTest::WalkingOnes | VerificationTest::WalkingZeros => {
+                for bit in 0..u64::BITS {
+                    let mut pattern = 1 << bit;
+                    if test == VerificationTest::WalkingZeros {
+                        pattern = !pattern;
+                    }
+                    self.fill(false, |_, _| pattern)?;
+                    self.verify(test, false, |_, _| pattern, None::<fn(usize, usize) -> u64>)?;
+                }
+            }
+            VerificationTest::MovingInversions => {
+                for pattern in MOVING_INVERSION_PATTERNS {
+                    self.fill(false, |_, _| pattern)?;
+                    self.verify(test, false, |_, _| pattern, Some(|_, _| !pattern))?;
+                    self.verify(test, true, |_, _| !pattern, Some(|_, _| pattern))?;
+                }
+            }
+            VerificationTest::AddressInAddress => {
+                let address = |_, address: usize| address as u64;
+                let inverted = |_, address: usize| !(address as u64);
+                self.fill(false, address)?;
+                self.verify(test, false, address, None::<fn(usize, usize) -> u64>)?;
+                self.fill(false, inverted)?;
+                self.verify(test, false, inverted, None::<fn(usize, usize) -> u64>)?;
+            }
+            VerificationTest::RandomPattern => {
+                let random = || {
+                    let mut rng = rand::rngs::SmallRng::seed_from_u64(seed);
+                    move |_: usize, _: usize| rng.next_u64()
+                };
+                let inverted = || {
+                    let mut random = random();
+                    move |idx: usize, address: usize| !random(idx, address)
+                };
+                self.fill(false, random())?;
+                self.verify(test, false, random(), Some(inverted()))?;
+                self.verify(test, false, inverted(), None::<fn(usize, usize) -> u64>)?;
+            }
+        }
+        Some(())
+    }
+    /// Writes `value(index, address)` to every word
+    fn fill(&mut self, descending: bool, mut value: impl FnMut(usize, usize) -> u64) -> Option<()> {
+        self.for_each_word(descending, |_, idx, word| unsafe {
+            word.write_volatile(value(idx, word.addr()));
+        })
+    }
+    /// Checks that every word holds `expected(index, address)`, recording a mismatch for every
+    /// one that doesn't, then writes `replace(index, address)` to it if given
+    fn verify(
+        &mut self,
+        test: VerificationTest,
+        descending: bool,
+        mut expected: impl FnMut(usize, usize) -> u64,
+        mut replace: Option<impl FnMut(usize, usize) -> u64>,
+    ) -> Option<()> {
+        self.for_each_word(descending, |result, idx, word| unsafe {
+            let expected = expected(idx, word.addr());
+            let observed = word.read_volatile();
+            if observed != expected {
+                result.record(Mismatch {
+                    test,
+                    offset: idx * size_of::<u64>(),
+                    expected,
+                    observed,
+                });
+            }
+            if let Some(replace) = &mut replace {
+                word.write_volatile(replace(idx, word.addr()));
+            }
+        })
+    }
+    /// Calls `visit` with the index of and a pointer to every word, reporting progress every
+    /// [`WORDS_PER_PROGRESS_UPDATE`] words.
+    ///
+    /// Returns `None` as soon as a stop was requested.
+    fn for_each_word(
+        &mut self,
+        descending: bool,
+        mut visit: impl FnMut(&mut VerificationResult, usize, *mut u64),
+    ) -> Option<()> {
+        let len = self.words.len();
+        let words = self.words.as_mut_ptr().cast::<u64>();
+        for chunk_start in (0..len).step_by(WORDS_PER_PROGRESS_UPDATE) {
+            let chunk = chunk_start..(chunk_start + WORDS_PER_PROGRESS_UPDATE).min(len);
+            let chunk_len = chunk.len();
+            let mut visit_idx = |idx: usize| visit(self.result, idx, unsafe { words.add(idx) });
+            if descending {
+                // Mirror the chunks as well, so that the whole buffer is visited back to front
+                let chunk = (len - chunk.end)..(len - chunk.start);
+                chunk.rev().for_each(&mut visit_idx);
+            } else {
+                chunk.for_each(&mut visit_idx);
+            }
+            self.progress.add(chunk_len as u64);
+            if self.progress.stop_requested() {
+                return None;
+            }
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Calls `test` with the words of a buffer of `len` words, returning what it reported
+    fn with_words(len: usize, test: impl FnOnce(&mut Words)) -> VerificationResult {
+        let mut words = vec![MaybeUninit::<u64>::uninit(); len];
+        let progress = ProgressTracker::new(0, 1, State::Allocating);
+        let mut result = VerificationResult::default();
+        test(&mut Words {
+            words: &mut words,
+            progress: &progress,
+            result: &mut result,
+        });
+        result
+    }
+
+    /// Flips bit 4 of byte `byte` of word `idx`, returning the value the word holds now
+    fn flip_byte(words: &mut Words, idx: usize, byte: usize) -> u64 {
+        let word = words.words[idx].as_mut_ptr();
+        unsafe {
+            word.cast::<u8>()
+                .add(byte)
+                .write_volatile(word.cast::<u8>().add(byte).read_volatile() ^ 0x10);
+            word.read_volatile()
+        }
+    }
+
+    #[test]
+    fn every_test_passes_on_good_memory() {
+        for &test in VerificationTest::all_values() {
+            let result = with_words(1000, |words| {
+                assert_eq!(words.run_test(test, 7), Some(()));
+            });
+            assert_eq!(result.mismatch_count, 0, "{}", test.as_str());
+            assert!(result.mismatches.is_empty(), "{}", test.as_str());
+        }
+    }
+
+    #[test]
+    fn reports_a_flipped_byte() {
+        let mut expected = 0;
+        let mut observed = 0;
+        let result = with_words(100, |words| {
+            let address = |_, address: usize| address as u64;
+            words.fill(false, address).unwrap();
+            expected = words.words[5].as_ptr().addr() as u64;
+            observed = flip_byte(words, 5, 3);
+            let test = VerificationTest::AddressInAddress;
+            words.verify(test, false, address, None::<fn(usize, usize) -> u64>);
+        });
+        assert_eq!(
+            observed,
+            expected ^ u64::from_ne_bytes([0, 0, 0, 0x10, 0, 0, 0, 0])
+        );
+        assert_eq!(result.mismatch_count, 1);
+        assert_eq!(
+            result.mismatches,
+            [Mismatch {
+                test: VerificationTest::AddressInAddress,
+                offset: 5 * size_of::<u64>(),
+                expected,
+                observed,
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_a_flipped_word_while_inverting() {
+        let pattern = MOVING_INVERSION_PATTERNS[1];
+        let test = VerificationTest::MovingInversions;
+        let result = with_words(100, |words| {
+            words.fill(false, |_, _| pattern).unwrap();
+            unsafe { words.words[9].as_mut_ptr().write_volatile(!pattern) };
+            words.verify(test, false, |_, _| pattern, Some(|_, _| !pattern));
+            // The faulty word was rewritten along with the others
+            words.verify(test, true, |_, _| !pattern, Some(|_, _| pattern));
+        });
+        assert_eq!(
+            result.mismatches,
+            [Mismatch {
+                test,
+                offset: 9 * size_of::<u64>(),
+                expected: pattern,
+                observed: !pattern,
+            }]
+        );
+        assert_eq!(result.mismatches[0].flipped_bits(), u64::MAX);
+    }
+
+    #[test]
+    fn counts_mismatches_past_the_recorded_ones() {
+        let len = MAX_RECORDED_MISMATCHES + 10;
+        let test = VerificationTest::WalkingOnes;
+        let result = with_words(len, |words| {
+            words.fill(false, |_, _| 0).unwrap();
+            words.verify(test, false, |_, _| 1, None::<fn(usize, usize) -> u64>);
+        });
+        assert_eq!(result.mismatch_count, len);
+        assert_eq!(result.mismatches.len(), MAX_RECORDED_MISMATCHES);
+        assert_eq!(result.mismatches[0].offset, 0);
+        assert_eq!(result.mismatches[0].observed, 0);
+        assert_eq!(result.mismatches[0].expected, 1);
+    }
+}
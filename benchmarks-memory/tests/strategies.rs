@@ -0,0 +1,248 @@
+//! Checks that every strategy reads, writes or copies exactly the bytes of the slice it is given,
+//! whatever its length and alignment.
+
+use benchmarks_core::SelectableEnum;
+use benchmarks_memory::{
+    MemoryOperation, OperationStrategy, ReadFn, ReadSink, WRITE_PATTERN, WriteFn,
+};
+use std::cell::RefCell;
+
+/// Lengths around the widths of every strategy, plus a few larger odd ones
+const LENGTHS: &[usize] = &[
+    0, 1, 2, 3, 7, 8, 15, 16, 17, 31, 32, 33, 63, 64, 65, 127, 128, 129, 255, 256, 1000, 4095,
+    4096, 4097, 16397,
+];
+/// Offsets from a page boundary covering every alignment up to a cache line
+const OFFSETS: std::ops::Range<usize> = 0..64;
+const CANARY: u8 = 0x5C;
+
+/// A buffer placed between two inaccessible pages, so that touching anything outside it faults
+struct Guarded {
+    mapping: *mut u8,
+    mapping_len: usize,
+    start: *mut u8,
+    len: usize,
+}
+
+impl Guarded {
+    /// A buffer of `len` bytes filled with [`CANARY`], starting `offset` bytes past a page
+    /// boundary, or ending right before the guard page after it if `offset` is `None`.
+    /// The bytes around the buffer up to the guard pages hold the canary as well.
+    fn new(len: usize, offset: Option<usize>) -> Self {
+        let page = page_size();
+        let accessible = (offset.unwrap_or(0) + len).next_multiple_of(page).max(page);
+        let mapping_len = accessible + 2 * page;
+        unsafe {
+            let mapping = libc::mmap(
+                std::ptr::null_mut(),
+                mapping_len,
+                libc::PROT_NONE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            );
+            assert_ne!(mapping, libc::MAP_FAILED, "mmap failed");
+            let mapping = mapping.cast::<u8>();
+            let accessible_start = mapping.add(page);
+            let protected = libc::mprotect(
+                accessible_start.cast(),
+                accessible,
+                libc::PROT_READ | libc::PROT_WRITE,
+            );
+            assert_eq!(protected, 0, "mprotect failed");
+            accessible_start.write_bytes(CANARY, accessible);
+            Self {
+                mapping,
+                mapping_len,
+                start: accessible_start.add(offset.unwrap_or(accessible - len)),
+                len,
+            }
+        }
+    }
+    fn bytes(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.start, self.len) }
+    }
+    /// Panics if anything around the buffer was touched
+    fn check_canaries(&self, what: &str) {
+        let page = page_size();
+        let accessible = unsafe {
+            std::slice::from_raw_parts(self.mapping.add(page), self.mapping_len - 2 * page)
+        };
+        let start = unsafe { self.start.offset_from(self.mapping.add(page)) } as usize;
+        let before = &accessible[..start];
+        let after = &accessible[start + self.len..];
+        assert!(
+            before.iter().chain(after).all(|&b| b == CANARY),
+            "{what} touched memory outside of its slice"
+        );
+    }
+}
+
+impl Drop for Guarded {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.mapping.cast(), self.mapping_len);
+        }
+    }
+}
+
+fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+thread_local! {
+    /// The address and size of every value [`Recorder`] was handed on this thread
+    static LOADS: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Records every value a read loads, after checking that it holds the bytes at its address
+struct Recorder;
+
+impl ReadSink for Recorder {
+    fn consume<T>(address: *const T, value: T) {
+        let size = size_of::<T>();
+        let loaded = unsafe { std::slice::from_raw_parts((&raw const value).cast::<u8>(), size) };
+        let memory = unsafe { std::slice::from_raw_parts(address.cast::<u8>(), size) };
+        assert_eq!(loaded, memory, "loaded a value that isn't at {address:?}");
+        LOADS.with_borrow_mut(|loads| loads.push((address as usize, size)));
+    }
+}
+
+/// The functions a strategy provides for every operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variant {
+    Regular,
+    NonTemporal,
+    Unaligned,
+}
+
+impl Variant {
+    fn read_fn(self, strategy: OperationStrategy) -> Option<ReadFn> {
+        match self {
+            Variant::Regular => strategy.read_fn_with::<Recorder>(false),
+            Variant::NonTemporal => strategy.read_fn_with::<Recorder>(true),
+            Variant::Unaligned => strategy.unaligned_read_fn_with::<Recorder>(),
+        }
+    }
+    fn write_fn(self, strategy: OperationStrategy) -> WriteFn {
+        match self {
+            Variant::Regular => strategy.write_fn(false),
+            Variant::NonTemporal => strategy.write_fn(true),
+            Variant::Unaligned => strategy.unaligned_write_fn(),
+        }
+    }
+    fn copy_fn(self, strategy: OperationStrategy) -> unsafe fn(*const u8, *mut u8, usize) -> usize {
+        match self {
+            Variant::Regular => strategy.copy_nonoverlapping_fn(false),
+            Variant::NonTemporal => strategy.copy_nonoverlapping_fn(true),
+            Variant::Unaligned => strategy.unaligned_copy_fn(),
+        }
+    }
+}
+
+/// Every enabled strategy, with every variant it supports
+fn strategies() -> impl Iterator<Item = (OperationStrategy, Variant)> {
+    OperationStrategy::all_values()
+        .iter()
+        .filter(|strategy| strategy.is_enabled())
+        .flat_map(|&strategy| {
+            [Variant::Regular, Variant::NonTemporal, Variant::Unaligned]
+                .into_iter()
+                .filter(move |&variant| {
+                    variant != Variant::NonTemporal || strategy.supports_non_temporal()
+                })
+                .map(move |variant| (strategy, variant))
+        })
+}
+
+/// The length and offset of every buffer the strategies get checked against, see [`Guarded::new`]
+fn buffers() -> impl Iterator<Item = (usize, Option<usize>)> {
+    LENGTHS.iter().flat_map(|&len| {
+        OFFSETS
+            .map(Some)
+            .chain([None])
+            .map(move |offset| (len, offset))
+    })
+}
+
+#[test]
+fn reads_cover_every_byte() {
+    for (strategy, variant) in strategies() {
+        let Some(read) = variant.read_fn(strategy) else {
+            assert!(
+                !strategy.supports_operation(MemoryOperation::Read),
+                "{strategy:?} ({variant:?}) has no read"
+            );
+            continue;
+        };
+        for (len, offset) in buffers() {
+            let mut memory = Guarded::new(len, offset);
+            for (idx, b) in memory.bytes().iter_mut().enumerate() {
+                *b = idx as u8 ^ 0x3C;
+            }
+            let what = format!("{strategy:?} read ({variant:?}, len: {len}, offset: {offset:?})");
+            LOADS.with_borrow_mut(Vec::clear);
+            let read_len = unsafe { read(memory.bytes().as_ptr(), len) };
+            assert_eq!(read_len, len, "{what} miscounted its bytes");
+            let start = memory.bytes().as_ptr() as usize;
+            let mut times_read = vec![0_usize; len];
+            for (address, size) in LOADS.take() {
+                let loaded = address
+                    .checked_sub(start)
+                    .map(|offset| offset..offset + size);
+                let Some(times_read) = loaded.and_then(|loaded| times_read.get_mut(loaded)) else {
+                    panic!("{what} loaded {size} bytes at {address:#x}, outside of its slice");
+                };
+                times_read.iter_mut().for_each(|times| *times += 1);
+            }
+            if let Some(idx) = times_read.iter().position(|&times| times != 1) {
+                panic!("{what} read byte {idx} {} times", times_read[idx]);
+            }
+            memory.check_canaries(&what);
+        }
+    }
+}
+
+#[test]
+fn writes_cover_every_byte() {
+    for (strategy, variant) in strategies() {
+        let write = variant.write_fn(strategy);
+        for (len, offset) in buffers() {
+            let mut memory = Guarded::new(len, offset);
+            let what = format!("{strategy:?} write ({variant:?}, len: {len}, offset: {offset:?})");
+            let written = unsafe { write(memory.bytes().as_mut_ptr(), len) };
+            assert_eq!(written, len, "{what} miscounted its bytes");
+            assert!(
+                memory.bytes().iter().all(|&b| b == WRITE_PATTERN),
+                "{what} missed bytes"
+            );
+            memory.check_canaries(&what);
+        }
+    }
+}
+
+#[test]
+fn copies_cover_every_byte() {
+    for (strategy, variant) in strategies() {
+        let copy = variant.copy_fn(strategy);
+        for (len, offset) in buffers() {
+            // Sources aligned differently than their destination take the unaligned load paths
+            let source_offset = match offset {
+                Some(offset) if offset % 2 == 0 => None,
+                Some(offset) => Some(offset * 7 % 64),
+                None => Some(0),
+            };
+            let mut from = Guarded::new(len, source_offset);
+            for (idx, b) in from.bytes().iter_mut().enumerate() {
+                *b = idx as u8 ^ 0x3C;
+            }
+            let mut to = Guarded::new(len, offset);
+            let what = format!("{strategy:?} copy ({variant:?}, len: {len}, offset: {offset:?})");
+            let copied = unsafe { copy(from.bytes().as_ptr(), to.bytes().as_mut_ptr(), len) };
+            assert_eq!(copied, len, "{what} miscounted its bytes");
+            assert_eq!(from.bytes(), to.bytes(), "{what} missed bytes");
+            from.check_canaries(&what);
+            to.check_canaries(&what);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::util::{parse_first_number, parse_from_bytes};
+use nix::{fcntl::OFlag, sys::stat::Mode};
+use std::{
+    io::{self, Error, ErrorKind},
+    path::Path,
+};
+use tracing::debug;
+
+/// The cache hierarchy as seen by the first CPU
+const CPU0_CACHE: &str = "/sys/devices/system/cpu/cpu0/cache/";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheType {
+    Data,
+    Instruction,
+    Unified,
+}
+
+#[derive(Debug, Clone)]
+pub struct CpuCache {
+    pub level: u8,
+    pub cache_type: CacheType,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheData {
+    /// Sorted by level, instruction caches last
+    pub caches: Vec<CpuCache>,
+}
+
+fn read_trimmed(path: &Path) -> io::Result<String> {
+    let mut text = std::fs::read_to_string(path)?;
+    text.truncate(text.trim_end().len());
+    Ok(text)
+}
+
+impl CpuCache {
+    fn get_from_path(path: &Path) -> io::Result<Self> {
+        let level: u8 = parse_from_bytes(read_trimmed(&path.join("level"))?.as_bytes())
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        let cache_type = match read_trimmed(&path.join("type"))?.as_str() {
+            "Data" => CacheType::Data,
+            "Instruction" => CacheType::Instruction,
+            "Unified" => CacheType::Unified,
+            other => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unknown cache type {other}"),
+                ));
+            }
+        };
+        let size = read_trimmed(&path.join("size"))?;
+        let (size, suffix) = parse_first_number(&size)?;
+        let multiplier = match suffix {
+            "" => 1,
+            "K" => 1024,
+            "M" => 1024 * 1024,
+            "G" => 1024 * 1024 * 1024,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unknown cache size suffix {suffix}"),
+                ));
+            }
+        };
+        Ok(CpuCache {
+            level,
+            cache_type,
+            size: size * multiplier,
+        })
+    }
+}
+
+impl CacheData {
+    pub fn fetch() -> io::Result<Self> {
+        let cache_dir = nix::dir::Dir::open(
+            CPU0_CACHE,
+            OFlag::O_RDONLY | OFlag::O_CLOEXEC,
+            Mode::empty(),
+        )?;
+        let mut caches = Vec::new();
+        for entry in cache_dir {
+            let entry = entry?;
+            let name = entry.file_name().to_bytes();
+            if !name.starts_with(b"index") {
+                continue;
+            }
+            let Ok(name) = core::str::from_utf8(name) else {
+                continue;
+            };
+            match CpuCache::get_from_path(&Path::new(CPU0_CACHE).join(name)) {
+                Ok(cache) => caches.push(cache),
+                Err(err) => debug!("Failed to get cache info: {err}"),
+            }
+        }
+        caches.sort_by_key(|cache| (cache.level, cache.cache_type == CacheType::Instruction));
+        Ok(CacheData { caches })
+    }
+}
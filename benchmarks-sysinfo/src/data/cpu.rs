@@ -31,6 +31,8 @@ pub struct CPUFeatures {
     pub avx2: bool,
     #[cfg(target_arch = "x86_64")]
     pub avx512: bool,
+    #[cfg(target_arch = "aarch64")]
+    pub neon: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -232,16 +234,21 @@ impl CpuData {
                         current_core.processor = processor;
                     }
                     "flags" => {
-                        if cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
+                        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                        {
                             current_core.features.sse = value.contains("sse");
                         }
-                        if cfg!(target_arch = "x86_64") {
+                        #[cfg(target_arch = "x86_64")]
+                        {
                             current_core.features.avx2 = value.contains("avx2");
-                        }
-                        if cfg!(target_arch = "x86_64") {
                             current_core.features.avx512 = value.contains("avx512f");
                         }
                     }
+                    // ARM lists its features under a different key, calling NEON "asimd"
+                    #[cfg(target_arch = "aarch64")]
+                    "Features" => {
+                        current_core.features.neon = value.split_whitespace().any(|f| f == "asimd");
+                    }
                     "bogomips" => {
                         let Ok(bogomips): Result<f32, _> = value.parse() else {
                             warn!(name: "Failed to parse CPUINFO bogomips number", value);
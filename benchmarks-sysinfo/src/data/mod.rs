@@ -1,3 +1,4 @@
+pub mod cache;
 pub mod cpu;
 pub mod disk;
 pub mod host;
@@ -253,9 +253,10 @@ pub struct SystemName {
 }
 
 #[must_use]
-fn up_to_null(slice: &[i8]) -> &[u8] {
-    // SAFETY: &[i8] and &[u8] have identical in-memory representation, valid bit patterns etc.
-    let slice: &[u8] = unsafe { &*(slice as *const [i8] as *const [u8]) };
+fn up_to_null(slice: &[core::ffi::c_char]) -> &[u8] {
+    // SAFETY: c_char is either i8 or u8, which have identical in-memory representation, valid bit
+    // patterns etc.
+    let slice: &[u8] = unsafe { std::slice::from_raw_parts(slice.as_ptr().cast(), slice.len()) };
     let len = slice.iter().position(|&b| b == 0).unwrap_or(slice.len());
     &slice[..len]
 }